@@ -1,213 +1,1402 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env::current_dir;
 use std::fs::{self, File};
-use std::io::{Read, BufReader, BufWriter, Write};
+use std::io::{Read, BufReader};
 use std::path::{PathBuf, Path};
+use std::process::Command;
 
-use crate::generators::{MergePage, HtmlGenerator};
+use crate::config::{SiteConfig, ListConfig, Schema, SCHEMA_FILE_NAME, parse_sort_order};
+use crate::generators::{Context, HtmlGenerator, Generator, GemtextRenderer, Template, Templates, TextRenderer};
 use crate::parsers::markdown::Lexer;
+use crate::parsers::metadata::{AfDateTime, Metadata, MetadataError, Value, split_front_matter};
+use crate::site::{MenuEntry, Page, Pager, Site, SortBy, build_menus, paginate, sort_pages};
 
-pub fn build(_args: &Vec<String>) {
+pub fn build(args: &[String]) {
+    let options = BuildOptions::from_args(args);
+    build_site(&options);
+}
+
+/// Builds the site in the current dir, exiting if the build fails.
+pub fn build_site(options: &BuildOptions) {
     let curr_dir_path = current_dir().unwrap();
-    build_internal(&curr_dir_path);
+    if let Err(error) = build_internal(&curr_dir_path, options) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+/// Flags passed to `build` and `run` that change which content is rendered.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BuildOptions {
+    /// Render pages marked `published: false`
+    pub drafts: bool,
+    /// Render pages with a `publishDate` in the future
+    pub future: bool,
+    /// Render pages with an `expiryDate` in the past
+    pub expired: bool,
+    /// Report schema violations as warnings instead of failing the build
+    pub warn_only: bool,
+    /// A config file to use instead of `afskylia.toml`, from `--config`
+    pub config: Option<PathBuf>,
+}
+
+impl BuildOptions {
+    /// Reads the flags following the command, e.g. `afskylia build --drafts`.
+    pub fn from_args(args: &[String]) -> BuildOptions {
+        let mut options = BuildOptions::default();
+        let mut args = args.iter().skip(2);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--drafts" => options.drafts = true,
+                "--future" => options.future = true,
+                "--expired" => options.expired = true,
+                "--warn-only" => options.warn_only = true,
+                "--config" => match args.next() {
+                    Some(path) => options.config = Some(PathBuf::from(path)),
+                    None => eprintln!("WARNING: --config needs a file"),
+                },
+                unknown => eprintln!("WARNING: Unknown flag \"{unknown}\""),
+            }
+        }
+        return options;
+    }
 }
 
-fn build_internal(base_dir: &PathBuf) -> HashMap<PathBuf, String> {
-    let mut layouts_map = get_layouts(&base_dir);
-    let content_map = get_content(&base_dir);
+fn build_internal(base_dir: &Path, options: &BuildOptions) -> Result<(), String> {
+    let config = SiteConfig::load(base_dir, options.config.as_deref())?;
+    let schema = Schema::load(base_dir)?;
+    let content_dir = &config.dirs.content;
+    let content_dir_path = base_dir.join(content_dir);
+    if !content_dir_path.is_dir() {
+        return Err(format!("ERROR: Content dir {} doesn't exist", content_dir_path.display()));
+    }
+    let mut layouts_map = get_layouts(&base_dir.join(&config.dirs.layouts), &config);
+    let now = AfDateTime::now();
+    let mut pages = Vec::new();
+    let mut skipped = BTreeSet::new();
+    let mut held_back = Vec::new();
+    let mut held_back_outputs = Vec::new();
+    for (key, content) in get_content(&content_dir_path, &config) {
+        let content_path = base_dir.join(content_dir).join(&key);
+        let modified = match config.git_lastmod {
+            true => get_git_lastmod(base_dir, &content_path).unwrap_or_else(|| get_modified(&content_path)),
+            false => get_modified(&content_path),
+        };
+        match parse_content(&content, modified, config.timezone) {
+            Ok(file) => match held_back_reason(&file.metadata, options, &now) {
+                None => match load_page(&key, file, &config) {
+                    Ok(page) => pages.push(page),
+                    Err(error) => {
+                        eprintln!("ERROR: {content_dir}/{key}: {error}");
+                        skipped.insert(key);
+                    }
+                },
+                Some(reason) => {
+                    held_back.push(format!("{content_dir}/{key} ({reason})"));
+                    held_back_outputs.push(Page::new(&key, &config.base_url, file.metadata, &file.body, file.lastmod).get_output_path());
+                    skipped.insert(key);
+                }
+            },
+            // A broken file is reported and left out instead of failing the whole build
+            Err(error) => {
+                eprintln!("ERROR: {}", error.with_path(&format!("{content_dir}/{key}")));
+                skipped.insert(key);
+            }
+        }
+    }
+
+    let site = Site::new(config, pages);
+    let config = &site.config;
+
+    if let Some(schema) = &schema {
+        schema.check_sections(&content_dir_path)?;
+        check_schema(schema, &site, options)?;
+    }
+
+    // A page whose own content is skipped is left out entirely, menu included
+    layouts_map.retain(|key, _| !key.strip_suffix(".html").is_some_and(|name| skipped.contains(&format!("{name}.md"))));
+
+    let public_dir_path = base_dir.join(&config.dirs.public);
+
+    if !public_dir_path.is_dir() {
+        fs::create_dir(&public_dir_path).expect("ERROR: Couldn't create public dir");
+    }
+
+    // A page built earlier with --drafts, --future or --expired mustn't be left behind
+    for output_path in &held_back_outputs {
+        remove_public_file(&public_dir_path, output_path);
+    }
+
+    copy_resources_to_public(&base_dir.join(&config.dirs.resources), &public_dir_path, config);
+    copy_page_assets(&content_dir_path, &public_dir_path, &site, &skipped);
+
+    let layouts_dir = &config.dirs.layouts;
+    let mut templates = Templates::new();
+    for (key, source) in layouts_map.iter() {
+        templates.insert(key, parse_layout(layouts_dir, key, source)?);
+    }
+    for (key, source) in [("_single.html", DEFAULT_SINGLE_LAYOUT), ("_list.html", DEFAULT_LIST_LAYOUT)] {
+        if templates.get(key).is_none() {
+            templates.insert(key, parse_layout(layouts_dir, key, source)?);
+        }
+    }
+
+    // Every top level page and section gets a page, as does a top level layout
+    // without content, e.g. `layouts/contact.html`
+    let mut names = layouts_map.keys()
+        .filter(|key| !key.starts_with('_') && !key.contains('/'))
+        .filter_map(|key| key.strip_suffix(".html").map(|x| x.to_string()))
+        .collect::<BTreeSet<String>>();
+    names.extend(site.get_pages().iter().filter(|x| x.section.is_empty()).map(|x| x.get_name().to_string()));
+    names.extend(site.get_sections().iter().map(|x| x.name.clone()));
+    let keys = names.iter().map(|x| format!("{x}.html")).collect::<Vec<String>>();
+    let menus = build_menus(&site)?;
+    // Without any menus configured, every page is listed like before
+    let all_pages_menu = build_menu_html(keys);
+    let site_value = site.to_value();
+    let mut sitemap_entries = Vec::new();
+
+    for file_name in &names {
+        let key = &format!("{file_name}.html");
+        let index = site.get_page(&format!("{file_name}.md"));
+        let section = site.get_section(file_name);
+        let layout = match section {
+            Some(_) => find_layout(&templates, index, Some(key), file_name, "list")?,
+            None => find_layout(&templates, index, Some(key), "", "single")?,
+        };
+        // A section's list page links to every post in it
+        let mut section_pages = section.map(|x| site.get_section_pages(x)).unwrap_or_default();
+        let (sort_by, descending, page_size) = get_list_settings(index, &config.lists);
+        sort_pages(&mut section_pages, &sort_by, descending);
+        let pagers = paginate(&section_pages, page_size, &format!("/{key}"), &format!("/{file_name}/"));
+
+        if config.gemtext {
+            let mut gemtext_content = index.map(|x| render_gemtext(x, config)).transpose()?.unwrap_or_default();
+            gemtext_content.push_str(&build_page_list_gemtext(&section_pages));
+            write_public_file(&public_dir_path, &format!("{file_name}.gmi"), &gemtext_content);
+        }
+
+        // A section was last modified when its newest post was
+        let lastmod = section_pages.iter().chain(index.iter()).map(|x| &x.lastmod).max();
+        let mut page_value = match index {
+            Some(page) => page.to_value(),
+            None => section_page_value(file_name, &format!("/{key}"), config),
+        };
+        if let Value::Table(table) = &mut page_value {
+            table.insert("lastmod".to_string(), Value::String(lastmod.map(|x| x.to_string()).unwrap_or_default()));
+        }
+        sitemap_entries.push((format!("/{key}"), lastmod.cloned()));
+
+        for pager in &pagers {
+            let list_html = build_page_list_html(&pager.pages);
+            let content = match index {
+                Some(page) => page.content.clone(),
+                None => list_html.clone(),
+            };
+
+            let mut context = Context::new();
+            context.insert_ref("site", &site_value);
+            context.insert_ref("page", &page_value);
+            insert_menus(&mut context, &menus, &all_pages_menu, &format!("/{key}"));
+            context.insert("content", Value::String(content));
+            context.insert("pages", Value::Array(pager.pages.iter().map(|x| x.to_value()).collect()));
+            context.insert("list", Value::String(list_html));
+            context.insert("pagination", Value::String(build_pagination_html(&pagers, pager.number)));
+            context.insert("paginator", Value::Table(BTreeMap::from([
+                ("number".to_string(), Value::Integer(pager.number as i64)),
+                ("total".to_string(), Value::Integer(pager.total as i64)),
+                ("prev".to_string(), Value::String(pager.prev_url.clone().unwrap_or_default())),
+                ("next".to_string(), Value::String(pager.next_url.clone().unwrap_or_default())),
+            ])));
+            let output = render_layout(&templates, &layout, &mut context)?;
+            write_public_file(&public_dir_path, &pager.get_output_path(), &output);
+        }
+    }
+
+    // Every post gets its own page, e.g. `public/posts/hello/index.html`
+    for page in site.get_pages().iter().filter(|x| !x.section.is_empty()) {
+        let mut context = Context::new();
+        context.insert_ref("site", &site_value);
+        insert_menus(&mut context, &menus, &all_pages_menu, &page.url);
+        context.insert("page", page.to_value());
+        context.insert("content", Value::String(page.content.clone()));
+        let layout = find_layout(&templates, Some(page), None, &page.section, "single")?;
+        let output = render_layout(&templates, &layout, &mut context)?;
+        write_public_file(&public_dir_path, &page.get_output_path(), &output);
+
+        if config.gemtext {
+            let output_path = Path::new(&page.get_output_path()).with_extension("gmi");
+            write_public_file(&public_dir_path, &output_path.to_string_lossy(), &render_gemtext(page, config)?);
+        }
+        sitemap_entries.push((page.url.clone(), Some(page.lastmod.clone())));
+    }
+
+    // A sitemap must list absolute urls, which need the base_url
+    match config.base_url.is_empty() {
+        true => eprintln!("WARNING: No base_url in the config, skipping sitemap.xml"),
+        false => write_sitemap(&public_dir_path, &config.base_url, &mut sitemap_entries),
+    }
+
+    if !held_back.is_empty() {
+        held_back.sort();
+        println!("Held back {} page(s):", held_back.len());
+        for page in &held_back {
+            println!("  {page}");
+        }
+    }
+
+    return Ok(());
+}
+
+/// Used for pages when the site has no `layouts/_single.html`.
+static DEFAULT_SINGLE_LAYOUT: &str = "{{ content }}";
+/// Used for sections when the site has no `layouts/_list.html`.
+static DEFAULT_LIST_LAYOUT: &str = "{{ content }}";
+
+/// The layout a page renders with, `kind` being `single` or `list`. The first
+/// that exists of: the front matter `layout`, the page's own layout (e.g.
+/// `about.html`), `<section>/<kind>.html`, `_default/<kind>.html` and
+/// `_<kind>.html`.
+fn find_layout(templates: &Templates, page: Option<&Page>, own_layout: Option<&str>, section: &str, kind: &str) -> Result<String, String> {
+    if let Some(page) = page {
+        if let Some(layout) = page.metadata.get_param("layout").and_then(|x| x.as_str()) {
+            let key = match layout.ends_with(".html") {
+                true => layout.to_string(),
+                false => format!("{layout}.html"),
+            };
+            return match templates.get(&key) {
+                Some(_) => Ok(key),
+                None => Err(format!("ERROR: {}: layout \"{key}\" doesn't exist", page.path)),
+            };
+        }
+    }
+
+    let mut candidates = own_layout.map(|x| x.to_string()).into_iter().collect::<Vec<String>>();
+    if !section.is_empty() {
+        candidates.push(format!("{section}/{kind}.html"));
+    }
+    candidates.push(format!("_default/{kind}.html"));
+    let fallback = format!("_{kind}.html");
+    return Ok(candidates.into_iter().find(|x| templates.get(x).is_some()).unwrap_or(fallback));
+}
+
+/// The placeholders layouts used before the template language, and what they
+/// are now.
+static LEGACY_PLACEHOLDERS: [(&str, &str); 16] = [
+    ("{content}", "{{ content }}"),
+    ("{layout}", "{{ layout }}"),
+    ("{menu}", "{{ menu }}"),
+    ("{title}", "{{ page.title }}"),
+    ("{date}", "{{ page.date }}"),
+    ("{description}", "{{ page.description }}"),
+    ("{lastmod}", "{{ page.lastmod }}"),
+    ("{site_title}", "{{ site.title }}"),
+    ("{base_url}", "{{ site.base_url }}"),
+    ("{language}", "{{ site.language }}"),
+    ("{pages}", "{{ list }}"),
+    ("{pagination}", "{{ pagination }}"),
+    ("{page_number}", "{{ paginator.number }}"),
+    ("{page_count}", "{{ paginator.total }}"),
+    ("{prev}", "{{ paginator.prev }}"),
+    ("{next}", "{{ paginator.next }}"),
+];
+
+/// Rewrites the old `{title}` style placeholders into template syntax, so
+/// existing layouts keep working. `{{ title }}` is left alone.
+fn upgrade_legacy_placeholders(source: &str) -> String {
+    let mut upgraded = String::with_capacity(source.len());
+    let mut rest = source;
+    'outer: while let Some(start) = rest.find('{') {
+        upgraded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let in_tag = upgraded.ends_with('{');
+        for (placeholder, replacement) in LEGACY_PLACEHOLDERS {
+            if !in_tag && rest.starts_with(placeholder) && !rest[placeholder.len()..].starts_with('}') {
+                upgraded.push_str(replacement);
+                rest = &rest[placeholder.len()..];
+                continue 'outer;
+            }
+        }
+        upgraded.push('{');
+        rest = &rest[1..];
+    }
+    upgraded.push_str(rest);
+    return upgraded;
+}
+
+fn parse_layout(layouts_dir: &str, key: &str, source: &str) -> Result<Template, String> {
+    return Template::parse(&format!("{layouts_dir}/{key}"), &upgrade_legacy_placeholders(source)).map_err(|e| format!("ERROR: {e}"));
+}
+
+/// Renders a page's layout, then `_base.html` around it as `layout` unless the
+/// layout extends another itself.
+fn render_layout(templates: &Templates, key: &str, context: &mut Context) -> Result<String, String> {
+    let html = templates.render(key, context).map_err(|e| format!("ERROR: {e}"))?;
+    let extends = templates.get(key).and_then(|x| x.get_parent()).is_some();
+    if extends || templates.get("_base.html").is_none() {
+        return Ok(html);
+    }
+    context.insert("layout", Value::String(html));
+    return templates.render("_base.html", context).map_err(|e| format!("ERROR: {e}"));
+}
+
+/// The `page` of a section list page without its own content file.
+fn section_page_value(name: &str, url: &str, config: &SiteConfig) -> Value {
+    let string = |s: &str| Value::String(s.to_string());
+    return Value::Table(BTreeMap::from([
+        ("path".to_string(), string("")),
+        ("name".to_string(), string(name)),
+        ("section".to_string(), string(name)),
+        ("url".to_string(), string(url)),
+        ("permalink".to_string(), Value::String(format!("{}{url}", config.base_url))),
+        ("title".to_string(), string(name)),
+        ("description".to_string(), string("")),
+        ("author".to_string(), string("")),
+        ("date".to_string(), string("")),
+        ("lastmod".to_string(), string("")),
+        ("weight".to_string(), Value::Integer(0)),
+        ("tags".to_string(), Value::Array(Vec::new())),
+        ("categories".to_string(), Value::Array(Vec::new())),
+        ("params".to_string(), Value::Table(BTreeMap::new())),
+        ("summary".to_string(), string("")),
+        ("summary_text".to_string(), string("")),
+        ("content".to_string(), string("")),
+        ("text".to_string(), string("")),
+    ]));
+}
+
+/// How a section's list is sorted and paginated: `sort_by`, `sort_order` and
+/// `page_size` from the front matter of its own page, else the site config.
+fn get_list_settings(index: Option<&Page>, lists: &ListConfig) -> (SortBy, Option<bool>, usize) {
+    let param = |key: &str| index.and_then(|x| x.metadata.get_param(key));
+
+    let sort_by = param("sort_by").and_then(|x| x.as_str()).unwrap_or(&lists.sort_by);
+    let descending = match param("sort_order").and_then(|x| x.as_str()) {
+        Some(order) => parse_sort_order(order).or(lists.descending),
+        None => lists.descending,
+    };
+    let page_size = match param("page_size").and_then(|x| x.as_integer()) {
+        Some(page_size) => usize::try_from(page_size).unwrap_or(lists.page_size),
+        None => lists.page_size,
+    };
+
+    return (SortBy::parse(sort_by), descending, page_size);
+}
+
+/// Writes a file to the public dir, creating the dirs it's in.
+/// Removes a page's html and Gemtext output, and its dir once that is empty,
+/// e.g. `public/posts/draft/`.
+fn remove_public_file(public_dir: &Path, output_path: &str) {
+    let path = public_dir.join(output_path);
+    for path in [path.clone(), path.with_extension("gmi")] {
+        if path.is_file() {
+            fs::remove_file(&path).unwrap_or_else(|e| panic!("ERROR: Couldn't remove {}: {e}", path.display()));
+        }
+    }
+    if let Some(parent) = path.parent().filter(|x| *x != public_dir) {
+        // Fails if anything else is still in there, which is then kept
+        let _ = fs::remove_dir(parent);
+    }
+}
+
+fn write_public_file(public_dir: &Path, output_path: &str, contents: &str) {
+    let path = public_dir.join(output_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap_or_else(|_| panic!("ERROR: Couldn't create dir for {output_path}"));
+    }
+    fs::write(&path, contents).unwrap_or_else(|_| panic!("ERROR: Couldn't write page {output_path}"));
+}
+
+/// Turns a parsed content file into a page with its markdown rendered.
+fn load_page(key: &str, file: ContentFile, config: &SiteConfig) -> Result<Page, String> {
+    let content = render_markdown(key, &file.body, file.line_offset, config)?;
+    let mut page = Page::new(key, &config.base_url, file.metadata, &file.body, file.lastmod);
+    let summary = page.get_raw_summary().map(|x| render_summary(x, config)).transpose()?;
+    page.set_content(content, summary);
+    let text = render_plain_text(&page.raw_content, config)?;
+    let summary_text = render_plain_text(page.get_raw_summary_or_first_paragraph(), config)?;
+    page.set_text(text, summary_text);
+    return Ok(page);
+}
+
+/// Validates every page that will be rendered and reports all violations at
+/// once, so a site can be fixed in one go.
+fn check_schema(schema: &Schema, site: &Site, options: &BuildOptions) -> Result<(), String> {
+    let mut violations = Vec::new();
+    for page in site.get_pages() {
+        for violation in schema.validate(&page.path, &page.metadata) {
+            violations.push(format!("{}/{}: {violation}", site.config.dirs.content, page.path));
+        }
+    }
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    violations.sort();
+    if options.warn_only {
+        for violation in &violations {
+            eprintln!("WARNING: {violation}");
+        }
+        return Ok(());
+    }
+
+    let lines = violations.iter().map(|x| format!("  {x}")).collect::<Vec<String>>().join("\n");
+    return Err(format!("ERROR: Front matter doesn't match {SCHEMA_FILE_NAME}, {} violation(s):\n{lines}", violations.len()));
+}
+
+/// Why a page is left out of this build, if it is: a draft, scheduled for
+/// later or expired. The build flags let each kind through.
+fn held_back_reason(metadata: &Metadata, options: &BuildOptions, now: &AfDateTime) -> Option<String> {
+    if !options.drafts && !metadata.is_published() {
+        return Some("draft".to_string());
+    }
+    if let Some(publish_date) = metadata.get_publish_date() {
+        if !options.future && publish_date > now {
+            return Some(format!("scheduled for {publish_date}"));
+        }
+    }
+    if let Some(expiry_date) = metadata.get_expiry_date() {
+        if !options.expired && expiry_date <= now {
+            return Some(format!("expired {expiry_date}"));
+        }
+    }
+    return None;
+}
+
+/// A content file split into its parsed front matter and markdown body.
+struct ContentFile {
+    metadata: Metadata,
+    body: String,
+    /// Lines taken up by the front matter, so warnings point at the right line
+    line_offset: usize,
+    /// The `lastmod` front matter key, else when the file was last modified
+    lastmod: AfDateTime,
+}
+
+/// Parses the front matter of a content file. `modified` is the date used
+/// when the front matter doesn't set `date` or `lastmod`, `utc_offset` is the
+/// site timezone.
+fn parse_content(content: &str, modified: AfDateTime, utc_offset: i32) -> Result<ContentFile, MetadataError> {
+    let (metadata, body, line_offset) = match split_front_matter(content) {
+        (Some(front_matter), body) => (Metadata::parse(front_matter, modified.clone(), utc_offset)?, body, front_matter.lines().count()),
+        (None, body) => (Metadata::new(modified.clone(), true), body, 0),
+    };
+    let lastmod = metadata.get_lastmod().cloned().unwrap_or(modified);
+
+    return Ok(ContentFile {
+        metadata,
+        body: body.to_string(),
+        line_offset,
+        lastmod,
+    });
+}
+
+fn get_modified(path: &Path) -> AfDateTime {
+    return match fs::metadata(path).and_then(|x| x.modified()) {
+        Ok(modified) => AfDateTime::from(modified),
+        Err(_) => AfDateTime::now(),
+    };
+}
+
+/// The date of the last commit touching the file, or `None` if git isn't
+/// installed, the site isn't a repository or the file isn't committed.
+fn get_git_lastmod(base_dir: &Path, path: &Path) -> Option<AfDateTime> {
+    let output = Command::new("git")
+        .arg("-C").arg(base_dir)
+        .args(["log", "-1", "--format=%cI", "--"])
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let date = String::from_utf8(output.stdout).ok()?;
+    return AfDateTime::parse(date.trim()).ok();
+}
+
+/// `entries` are site relative urls, e.g. `/posts/hello/`, with their lastmod.
+fn write_sitemap(public_dir: &Path, base_url: &str, entries: &mut [(String, Option<AfDateTime>)]) {
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut sitemap = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    sitemap.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for (page, lastmod) in entries.iter() {
+        sitemap.push_str(&format!("  <url>\n    <loc>{}</loc>\n", escape_xml(&format!("{base_url}{page}"))));
+        if let Some(lastmod) = lastmod {
+            sitemap.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod.to_rfc3339()));
+        }
+        sitemap.push_str("  </url>\n");
+    }
+    sitemap.push_str("</urlset>\n");
+
+    fs::write(public_dir.join("sitemap.xml"), sitemap).expect("ERROR: couldn't write sitemap");
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+fn render_markdown(content_path: &str, markdown_content: &str, line_offset: usize, config: &SiteConfig) -> Result<String, String> {
+    let lexer = Lexer::new(markdown_content)?;
+    let mut html_generator = HtmlGenerator::with_extensions(lexer, config.markdown);
+    let html_content = html_generator.get_html()?;
+
+    for warning in html_generator.get_warnings() {
+        eprintln!("WARNING: {}/{content_path}:{}:{}: {}", config.dirs.content, warning.line + line_offset, warning.column, warning.kind);
+    }
+
+    return Ok(html_content);
+}
+
+/// Renders the part of a page before `<!--more-->`. Its warnings were already
+/// reported for the whole page.
+fn render_summary(markdown_content: &str, config: &SiteConfig) -> Result<String, String> {
+    let lexer = Lexer::new(markdown_content)?;
+    let mut html_generator = HtmlGenerator::with_extensions(lexer, config.markdown);
+    return html_generator.get_html();
+}
+
+fn render_plain_text(markdown_content: &str, config: &SiteConfig) -> Result<String, String> {
+    let lexer = Lexer::new(markdown_content)?;
+    let mut generator = Generator::with_renderer(lexer, config.markdown, TextRenderer);
+    return Ok(generator.generate()?.trim_end().to_string());
+}
+
+fn render_gemtext(page: &Page, config: &SiteConfig) -> Result<String, String> {
+    let lexer = Lexer::new(&page.raw_content).map_err(|e| format!("ERROR: {}/{}: {e}", config.dirs.content, page.path))?;
+    let mut generator = Generator::with_renderer(lexer, config.markdown, GemtextRenderer::new());
+    return generator.generate().map_err(|e| format!("ERROR: {}/{}: {e}", config.dirs.content, page.path));
+}
+
+/// Copies everything in the resources dir to the public dir, keeping its dirs,
+/// e.g. `resources/img/logo.png` to `public/img/logo.png`. A site without a
+/// resources dir has nothing to copy.
+fn copy_resources_to_public(resources_dir: &Path, public_dir: &Path, config: &SiteConfig) {
+    for file in list_files(resources_dir, config) {
+        copy_file(&resources_dir.join(&file), &public_dir.join(&file));
+    }
+}
+
+/// Copies the files in the content dir that aren't pages. Files in a page's
+/// bundle, the dir named like the page (e.g. `posts/hello/cover.png` for
+/// `posts/hello.md`), go next to the rendered page so relative links work.
+/// Anything else keeps its path, and held back pages' files aren't copied.
+fn copy_page_assets(content_dir: &Path, public_dir: &Path, site: &Site, skipped: &BTreeSet<String>) {
+    'files: for file in list_files(content_dir, &site.config) {
+        if file.extension().is_some_and(|x| x == "md") {
+            continue;
+        }
+
+        for bundle_dir in file.ancestors().skip(1) {
+            let page_path = format!("{}.md", bundle_dir.display());
+            if skipped.contains(&page_path) {
+                continue 'files;
+            }
+            if let Some(page) = site.get_page(&page_path).filter(|x| !x.section.is_empty()) {
+                let rest = file.strip_prefix(bundle_dir).expect("ERROR: Couldn't strip bundle dir");
+                copy_file(&content_dir.join(&file), &public_dir.join(page.url.trim_matches('/')).join(rest));
+                continue 'files;
+            }
+        }
+        copy_file(&content_dir.join(&file), &public_dir.join(&file));
+    }
+}
+
+fn copy_file(from: &Path, to: &Path) {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir).expect("ERROR: Couldn't create dir in public");
+    }
+    fs::copy(from, to).expect("ERROR: couldn't copy file");
+}
+
+/// Every file under `dir` as a path relative to it, in name order. Files
+/// matching `ignore_files` are left out, and a missing dir has no files.
+fn list_files(dir: &Path, config: &SiteConfig) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    list_files_rec(dir, dir, config, &mut files);
+    return files;
+}
+
+fn list_files_rec(dir: &Path, base_dir: &Path, config: &SiteConfig, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths = entries.map(|x| x.expect("ERROR: couldn't get path").path()).collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    for path in paths {
+        if config.is_ignored(&path) {
+            continue;
+        }
+        match path.is_dir() {
+            true => list_files_rec(&path, base_dir, config, files),
+            false => files.push(get_relative_file_path(&path, base_dir)),
+        }
+    }
+}
+
+/// Every markdown file in the content dir by its path, e.g. `posts/hello.md`.
+fn get_content(content_dir_path: &Path, config: &SiteConfig) -> BTreeMap<String, String> {
+    let markdown_files = list_files(content_dir_path, config).into_iter().filter(|x| x.extension().is_some_and(|x| x == "md"));
+    return read_files(content_dir_path, markdown_files);
+}
+
+//TODO: Should probably have a struct instead of string, to support ordering and custom naming
+fn build_menu_html(pages: Vec<String>) -> String {
+    let mut menu_html = String::from("<ul>");
+    for page in pages {
+        let page_name = page.replace(".html", "");
+        let menu_item = format!("<li><a href=\"/{page}\">{page_name}</a></li>");
+        menu_html.push_str(&menu_item);
+    }
+    menu_html.push_str("</ul>");
+    return menu_html;
+}
+
+/// Adds `menus`, every menu by name, and `menu`, the main menu as html.
+fn insert_menus(context: &mut Context, menus: &BTreeMap<String, Vec<MenuEntry>>, all_pages_menu: &str, current_url: &str) {
+    let menus_value = menus.iter()
+        .map(|(name, entries)| (name.clone(), Value::Array(entries.iter().map(|x| x.to_value(current_url)).collect())))
+        .collect();
+    context.insert("menus", Value::Table(menus_value));
+
+    let menu_html = match menus.is_empty() {
+        true => all_pages_menu.to_string(),
+        false => build_menu_entries_html(menus.get("main").map(Vec::as_slice).unwrap_or_default(), current_url),
+    };
+    context.insert("menu", Value::String(menu_html));
+}
+
+/// A nested `<ul>` of menu entries, marking the current page's entry.
+fn build_menu_entries_html(entries: &[MenuEntry], current_url: &str) -> String {
+    if entries.is_empty() {
+        return String::default();
+    }
+
+    let mut menu_html = String::from("<ul>");
+    for entry in entries {
+        let current = match entry.url == current_url {
+            true => " aria-current=\"page\"",
+            false => "",
+        };
+        menu_html.push_str(&format!("<li><a href=\"{}\"{current}>{}</a>", entry.url, entry.name));
+        menu_html.push_str(&build_menu_entries_html(&entry.children, current_url));
+        menu_html.push_str("</li>");
+    }
+    menu_html.push_str("</ul>");
+    return menu_html;
+}
+
+fn build_page_list_html(pages: &[&Page]) -> String {
+    if pages.is_empty() {
+        return String::default();
+    }
+
+    let mut list_html = String::from("<ul>");
+    for page in pages {
+        list_html.push_str(&format!("<li><a href=\"{}\">{}</a></li>", page.url, page.get_title()));
+    }
+    list_html.push_str("</ul>");
+    return list_html;
+}
+
+/// Links to the previous, next and every numbered page of a list, or nothing
+/// if the list fits on one page.
+fn build_pagination_html(pagers: &[Pager], current: usize) -> String {
+    if pagers.len() < 2 {
+        return String::default();
+    }
+
+    let mut pagination_html = String::from("<nav class=\"pagination\">");
+    if let Some(prev_url) = &pagers[current - 1].prev_url {
+        pagination_html.push_str(&format!("<a href=\"{prev_url}\" rel=\"prev\">Previous</a>"));
+    }
+    for pager in pagers {
+        match pager.number == current {
+            true => pagination_html.push_str(&format!("<span aria-current=\"page\">{}</span>", pager.number)),
+            false => pagination_html.push_str(&format!("<a href=\"{}\">{}</a>", pager.url, pager.number)),
+        }
+    }
+    if let Some(next_url) = &pagers[current - 1].next_url {
+        pagination_html.push_str(&format!("<a href=\"{next_url}\" rel=\"next\">Next</a>"));
+    }
+    pagination_html.push_str("</nav>");
+    return pagination_html;
+}
+
+fn build_page_list_gemtext(pages: &[&Page]) -> String {
+    return pages.iter().map(|x| format!("=> {} {}\n", x.url, x.get_title())).collect();
+}
+
+fn read_files(base_dir: &Path, files: impl Iterator<Item = PathBuf>) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    for relative_file_path in files {
+        let file = File::open(base_dir.join(&relative_file_path)).expect("ERROR: Couldn't open file");
+        let mut buf_reader = BufReader::new(file);
+                                                                                                                                                     
+        let mut content = String::new();
+        buf_reader.read_to_string(&mut content).expect("ERROR: Couldn't read file to string");
+
+        map.insert(relative_file_path.into_os_string().into_string().expect("ERROR: Couldn't convert path to string"), content);
+    }
+    return map;
+}
+
+/// Every layout by its path in the layouts dir, e.g. `partials/nav.html`.
+fn get_layouts(layouts_dir_path: &Path, config: &SiteConfig) -> BTreeMap<String, String> {
+    // Other files, like a stray `notes.txt`, aren't layouts
+    let html_files = list_files(layouts_dir_path, config).into_iter().filter(|x| x.extension().is_some_and(|x| x == "html"));
+    return read_files(layouts_dir_path, html_files);
+}
+
+fn get_relative_file_path(file_path: &Path, base_path: &Path) -> PathBuf {
+    let file_path = file_path.strip_prefix(base_path).expect("ERROR: Couldn't strip prefix");
+    return file_path.to_path_buf();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    use claim::{assert_ok, assert_some};
+
+    use crate::commands::test_utils::*;
+    use crate::commands::build::*;
+
+    #[test]
+    fn parse_content_strips_front_matter() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: true\n\
+                     +++\n\
+                     # Hello";
+        let file = parse_content(input, AfDateTime::now(), 0).expect("ERROR: Couldn't parse content");
+
+        assert_eq!(file.body, "# Hello");
+        assert_eq!(file.line_offset, 4);
+        assert_eq!(file.metadata.get_date().to_string(), "2023-04-08T10:17:00");
+    }
+
+    #[test]
+    fn parse_content_without_date_uses_modified() {
+        let input = "+++\n\
+                     title: Hello\n\
+                     +++\n\
+                     # Hello";
+        let modified = AfDateTime::from("2023-04-08T10:17:00");
+        let file = parse_content(input, modified.clone(), 0).expect("ERROR: Couldn't parse content");
+
+        assert_eq!(file.metadata.get_date(), &modified);
+        assert!(file.metadata.is_published());
+    }
+
+    #[test]
+    fn build_skips_content_with_invalid_front_matter() {
+        let broken_content = "+++\n\
+                              published: yes\n\
+                              +++\n\
+                              # Broken";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "{menu}{content}", INDEX_CONTENT)
+            .with_page_with_content("broken", INDEX_LAYOUT, broken_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let broken_exists = public_dir_path.join("broken.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(!broken_exists, "Broken page was rendered");
+        assert!(!index_file_str.contains("broken.html"), "Menu links to broken page");
+    }
+
+    #[test]
+    fn build_renders_plain_text_for_layouts() {
+        let page_content = "# Hello\n\
+                            \n\
+                            A [link](https://example.com) with `code`\n\
+                            \n\
+                            Rest";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", "{{ layout }}")
+            .with_page_with_content("index", "<meta content=\"{{ page.summary_text }}\">{{ page.text }}", page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let index_file_str = fs::read_to_string(base_dir_path.join("public/index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.starts_with("<meta content=\"A link with code\">Hello\n\nA link with code\n\nRest"), "Wrong plain text: {index_file_str}");
+    }
+
+    #[test]
+    fn build_with_empty_content_file() {
+        let site_builder = SiteBuilder::new()
+            .with_config("[output]\ngemtext = true\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, "")
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "empty", "");
+
+        let base_dir_path = site_builder.get_path();
+
+        let result = build_internal(&base_dir_path, &BuildOptions::default());
+        let index_exists = base_dir_path.join("public/index.html").exists();
+        let post_exists = base_dir_path.join("public/posts/empty/index.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_ok!(result);
+        assert!(index_exists, "Empty page wasn't rendered");
+        assert!(post_exists, "Empty post wasn't rendered");
+    }
+
+    #[test]
+    fn build_with_schema_violations_fails_unless_warn_only() {
+        let page_content = "---\n\
+                            title: Home\n\
+                            rating: high\n\
+                            ---\n\
+                            # Index";
+        let site_builder = SiteBuilder::new()
+            .with_schema("[*]\ntitle = \"string\"\nsummary = \"string\"\nrating = \"integer?\"\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        let result = build_internal(&base_dir_path, &BuildOptions::default());
+        let index_exists = base_dir_path.join("public").join("index.html").exists();
+        let warn_only_result = build_internal(&base_dir_path, &BuildOptions { warn_only: true, ..BuildOptions::default() });
+        let index_exists_warn_only = base_dir_path.join("public").join("index.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        let error = result.expect_err("ERROR: Build with violations succeeded");
+        assert!(error.contains("2 violation(s)"), "Wrong violation count: {error}");
+        assert!(error.contains("content/index.md: key \"rating\" should be integer"), "Missing type violation: {error}");
+        assert!(error.contains("content/index.md: missing required key \"summary\""), "Missing required key violation: {error}");
+        assert!(!index_exists, "Page was rendered despite violations");
+        assert_ok!(warn_only_result);
+        assert!(index_exists_warn_only, "Page wasn't rendered with --warn-only");
+    }
+
+    #[test]
+    fn build_with_schema_section_glob_and_unknown_section() {
+        let site_builder = SiteBuilder::new()
+            .with_schema("[posts/*]\ntitle = \"string\"\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "a", "+++\ndate = 2023-04-08\n+++\n# A");
+
+        let base_dir_path = site_builder.get_path();
+
+        let glob_result = build_internal(&base_dir_path, &BuildOptions::default());
+        fs::write(base_dir_path.join("schema.toml"), "[post]\ntitle = \"string\"\n").expect("ERROR: couldn't write schema file");
+        let unknown_result = build_internal(&base_dir_path, &BuildOptions::default());
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        let error = glob_result.expect_err("ERROR: Build with violations succeeded");
+        assert!(error.contains("content/posts/a.md: missing required key \"title\""), "Missing required key violation: {error}");
+        let error = unknown_result.expect_err("ERROR: Build with unknown schema section succeeded");
+        assert!(error.contains("[post]"), "Wrong error: {error}");
+    }
+
+    #[test]
+    fn build_content_with_front_matter_renders_metadata() {
+        let page_content = "+++\n\
+                            date: 2023-04-08T10:17:00\n\
+                            published: true\n\
+                            +++\n\
+                            # Hello";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "<time>{date}</time>{content}", page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<time>2023-04-08T10:17:00</time><h1>Hello</h1>"), "Does not contain date and content");
+        assert!(!index_file_str.contains("published"), "Contains front matter");
+    }
+
+    #[test]
+    fn build_replaces_title_and_description() {
+        let page_content = "+++\n\
+                            date: 2023-04-08T10:17:00\n\
+                            published: true\n\
+                            title: \"Welcome home\"\n\
+                            description: The start page\n\
+                            +++\n\
+                            # Hello";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "<h2>{title}</h2><p>{description}</p>{content}", page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<h2>Welcome home</h2><p>The start page</p>"), "Does not contain title and description");
+    }
+    #[test]
+    fn build_content_with_yaml_front_matter() {
+        let page_content = "---\n\
+                            date: 2023-04-08T10:17:00\n\
+                            published: true\n\
+                            title: From YAML\n\
+                            ---\n\
+                            # Hello";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "<h2>{title}</h2>{content}", page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<h2>From YAML</h2><h1>Hello</h1>"), "Does not contain title and content");
+    }
+
+    static DRAFT_CONTENT: &str = "+++\n\
+                                  date: 2023-04-08T10:17:00\n\
+                                  published: false\n\
+                                  +++\n\
+                                  # Draft";
+
+    #[test]
+    fn build_skips_unpublished_page_and_menu_entry() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "{menu}{content}", INDEX_CONTENT)
+            .with_page_with_content("draft", INDEX_LAYOUT, DRAFT_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let draft_exists = public_dir_path.join("draft.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(!draft_exists, "Draft page was rendered");
+        assert!(!index_file_str.contains("draft.html"), "Menu links to draft");
+    }
+
+    #[test]
+    fn build_with_drafts_renders_unpublished_page() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "{menu}{content}", INDEX_CONTENT)
+            .with_page_with_content("draft", INDEX_LAYOUT, DRAFT_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions { drafts: true, ..BuildOptions::default() }).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let draft_file_str = fs::read_to_string(public_dir_path.join("draft.html")).expect("ERROR: Couldn't read draft.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(draft_file_str.contains("<h1>Draft</h1>"), "Draft page was not rendered");
+        assert!(index_file_str.contains("draft.html"), "Menu does not link to draft");
+    }
+
+    #[test]
+    fn build_without_drafts_removes_draft_output() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "{menu}{content}", INDEX_CONTENT)
+            .with_page_with_content("about", INDEX_LAYOUT, DRAFT_CONTENT)
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "post-1", POST_1_CONTENT)
+            .with_nested_content(PathBuf::from("posts"), "draft", DRAFT_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+        let public_dir_path = base_dir_path.join("public");
+
+        build_internal(&base_dir_path, &BuildOptions { drafts: true, ..BuildOptions::default() }).expect("ERROR: Build with drafts failed");
+        let draft_built = public_dir_path.join("posts/draft/index.html").exists() && public_dir_path.join("about.html").exists();
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+        let draft_exists = public_dir_path.join("posts/draft").exists() || public_dir_path.join("about.html").exists();
+        let post_exists = public_dir_path.join("posts/post-1/index.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(draft_built, "Draft was not built with --drafts");
+        assert!(!draft_exists, "Draft output was left in public dir");
+        assert!(post_exists, "Published post was not built");
+    }
+
+    #[test]
+    fn build_section_skips_unpublished_posts() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "post-1", POST_1_CONTENT)
+            .with_nested_content(PathBuf::from("posts"), "post-2", DRAFT_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let posts_file_str = fs::read_to_string(public_dir_path.join("posts.html")).expect("ERROR: Couldn't read posts.html");
+        let draft_exists = public_dir_path.join("posts/post-2/index.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(posts_file_str.contains("<a href=\"/posts/post-1/\">post-1</a>"), "Published post missing");
+        assert!(!posts_file_str.contains("post-2"), "Section links to draft");
+        assert!(!draft_exists, "Draft was rendered");
+    }
+
+    #[test]
+    fn build_section_renders_each_post_and_list_page() {
+        let site_builder = SiteBuilder::new()
+            .with_config("base_url = \"https://example.com\"\n")
+            .with_base_layout("base", BASE)
+            .with_base_layout("single", "<article>{title}{content}</article>")
+            .with_page_with_nested_content("posts", "<h1>{title}</h1>{pages}", PathBuf::from("posts"), "post-1", POST_1_CONTENT)
+            .with_nested_content(PathBuf::from("posts"), "post-2", "+++\ntitle = \"Second\"\n+++\n# Post 2");
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let posts_file_str = fs::read_to_string(public_dir_path.join("posts.html")).expect("ERROR: Couldn't read posts.html");
+        let post_1_str = fs::read_to_string(public_dir_path.join("posts/post-1/index.html")).expect("ERROR: Couldn't read post-1");
+        let post_2_str = fs::read_to_string(public_dir_path.join("posts/post-2/index.html")).expect("ERROR: Couldn't read post-2");
+        let sitemap_str = fs::read_to_string(public_dir_path.join("sitemap.xml")).expect("ERROR: Couldn't read sitemap.xml");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(posts_file_str.contains("<h1>posts</h1><ul><li>"), "List page has no list");
+        assert!(posts_file_str.contains("<li><a href=\"/posts/post-1/\">post-1</a></li>"), "List page doesn't link to post 1");
+        assert!(posts_file_str.contains("<li><a href=\"/posts/post-2/\">Second</a></li>"), "List page doesn't link to post 2");
+        assert!(!posts_file_str.contains("Post content"), "List page contains post content");
+        assert!(post_1_str.contains("<article>post-1<h1>Post 1</h1>"), "Post 1 not rendered with single layout");
+        assert!(!post_1_str.contains("Post 2"), "Post 1 contains post 2");
+        assert!(post_2_str.contains("<article>Second<h1>Post 2</h1>"), "Post 2 not rendered with single layout");
+        assert!(sitemap_str.contains("<loc>https://example.com/posts/post-2/</loc>"), "Sitemap doesn't list posts");
+    }
+
+    #[test]
+    fn build_section_list_is_sorted_and_paginated() {
+        let site_builder = SiteBuilder::new()
+            .with_config("[lists]\npage_size = 2\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_nested_content("posts", "{content}{pages}|{page_number}/{page_count}|{prev}|{next}|{pagination}", PathBuf::from("posts"), "a", "+++\ntitle = \"Cherry\"\n+++\n")
+            .with_nested_content(PathBuf::from("posts"), "b", "+++\ntitle = \"Apple\"\n+++\n")
+            .with_nested_content(PathBuf::from("posts"), "c", "+++\ntitle = \"Banana\"\n+++\n")
+            .with_nested_content(PathBuf::new(), "posts", "+++\nsort_by = \"title\"\n+++\nFruit");
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let first_str = fs::read_to_string(public_dir_path.join("posts.html")).expect("ERROR: Couldn't read posts.html");
+        let second_str = fs::read_to_string(public_dir_path.join("posts/page/2/index.html")).expect("ERROR: Couldn't read page 2");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(first_str.contains("<p>Fruit</p><ul><li><a href=\"/posts/b/\">Apple</a></li><li><a href=\"/posts/c/\">Banana</a></li></ul>|1/2||/posts/page/2/|"), "Wrong first page: {first_str}");
+        assert!(first_str.contains("<span aria-current=\"page\">1</span><a href=\"/posts/page/2/\">2</a><a href=\"/posts/page/2/\" rel=\"next\">Next</a>"), "Wrong pagination: {first_str}");
+        assert!(second_str.contains("<ul><li><a href=\"/posts/a/\">Cherry</a></li></ul>|2/2|/posts.html||"), "Wrong second page: {second_str}");
+    }
+
+    #[test]
+    fn build_with_template_syntax_renders_loops_and_filters() {
+        let site_builder = SiteBuilder::new()
+            .with_config("title = \"Fruit Stand\"\n")
+            .with_base_layout("base", "<title>{{ page.title }} | {{ site.title | upper }}</title>{{ layout }}")
+            .with_base_layout("single", "{% for tag in page.tags %}{% if not loop.first %}, {% endif %}#{{ tag }}{% endfor %}{{ content }}")
+            .with_page_with_nested_content("posts", "{% for post in pages %}<a href=\"{{ post.url }}\">{{ post.title | default(post.name) }}</a>{% endfor %}", PathBuf::from("posts"), "a", "+++\ntitle = \"Apple\"\ntags = [\"red\", \"sweet\"]\n+++\nCrunchy");
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let posts_file_str = fs::read_to_string(public_dir_path.join("posts.html")).expect("ERROR: Couldn't read posts.html");
+        let post_str = fs::read_to_string(public_dir_path.join("posts/a/index.html")).expect("ERROR: Couldn't read post");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(posts_file_str, "<title>posts | FRUIT STAND</title><a href=\"/posts/a/\">Apple</a>");
+        assert_eq!(post_str, "<title>Apple | FRUIT STAND</title>#red, #sweet<p>Crunchy</p>");
+    }
+
+    #[test]
+    fn build_with_template_error_fails_with_layout_and_line() {
+        let site_builder = SiteBuilder::new()
+            .with_page_with_content("index", "<h1>{{ page.title }}</h1>\n{{ page.subtitle }}", INDEX_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        let result = build_internal(&base_dir_path, &BuildOptions::default());
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(result.expect_err("ERROR: Build with undefined variable succeeded"), "ERROR: layouts/index.html:2: undefined variable \"page.subtitle\"");
+    }
+
+    #[test]
+    fn build_with_partials_and_extends_renders_blocks() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", "<head>{% block head %}<title>{{ site.title }}</title>{% endblock %}</head>{% block main %}{{ layout }}{% endblock %}{% block footer %}{% include \"footer.html\" year=2024 %}{% endblock %}")
+            .with_partial("footer", "<footer>{{ year }} {{ page.title }}</footer>")
+            .with_page_with_content("about", "{% extends \"_base.html\" %}{% block head %}<title>{{ page.title }}</title>{% endblock %}{% block main %}<main>{{ content }}</main>{% endblock %}", "+++\ntitle = \"About\"\n+++\nHi")
+            .with_page_with_content("index", "<main>{{ content }}</main>", "+++\ntitle = \"Home\"\n+++\nWelcome");
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let about_str = fs::read_to_string(public_dir_path.join("about.html")).expect("ERROR: Couldn't read about.html");
+        let index_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let partial_exists = public_dir_path.join("partials").exists() || public_dir_path.join("footer.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(about_str, "<head><title>About</title></head><main><p>Hi</p></main><footer>2024 About</footer>");
+        assert_eq!(index_str, "<head><title></title></head><main><p>Welcome</p></main><footer>2024 Home</footer>");
+        assert!(!partial_exists, "Partial was rendered as a page");
+    }
+
+    #[test]
+    fn build_finds_layouts_by_front_matter_section_and_default() {
+        let site_builder = SiteBuilder::new()
+            .with_layout("posts/single.html", "post:{{ page.title }}")
+            .with_layout("_default/single.html", "default:{{ page.title }}")
+            .with_layout("_default/list.html", "list:{{ page.title }}{{ list }}")
+            .with_layout("special.html", "special:{{ page.title }}")
+            .with_nested_content(PathBuf::new(), "about", "+++\ntitle = \"About\"\n+++\n")
+            .with_nested_content(PathBuf::new(), "posts", "+++\ntitle = \"Posts\"\n+++\n")
+            .with_page_with_nested_content("notes", "own:{{ page.title }}", PathBuf::from("notes"), "c", "+++\ntitle = \"C\"\n+++\n")
+            .with_nested_content(PathBuf::from("posts"), "a", "+++\ntitle = \"A\"\n+++\n")
+            .with_nested_content(PathBuf::from("posts"), "b", "+++\ntitle = \"B\"\nlayout = \"special\"\n+++\n");
+
+        let base_dir_path = site_builder.get_path();
 
-    let public_dir_path = base_dir.join(PUBLIC_DIR_PATH);
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-    if !public_dir_path.is_dir() {
-        fs::create_dir(&public_dir_path).expect("ERROR: Couldn't create public dir");
+        let public_dir_path = base_dir_path.join("public");
+        let read = |path: &str| fs::read_to_string(public_dir_path.join(path)).unwrap_or_else(|_| panic!("ERROR: Couldn't read {path}"));
+        let outputs = ["about.html", "posts.html", "notes.html", "posts/a/index.html", "posts/b/index.html", "notes/c/index.html"].map(read);
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(outputs[0], "default:About");
+        assert!(outputs[1].starts_with("list:Posts<ul>"), "Section without own layout doesn't use _default/list.html");
+        assert_eq!(outputs[2], "own:notes");
+        assert_eq!(outputs[3], "post:A");
+        assert_eq!(outputs[4], "special:B");
+        assert_eq!(outputs[5], "default:C");
     }
 
-    copy_resources_to_public(&base_dir.join("resources"), &public_dir_path);
-
-    let maybe_base_template = layouts_map.remove("_base.html");
-    let keys = layouts_map.clone().into_keys().collect::<Vec<String>>();
-    let menu_html = build_menu_html(keys);
-
-    for (key, value) in layouts_map.iter() {
-        let file_name = &key[..=key.len()-6];
-        let content_key = &format!("{file_name}.md");
-        let markdown_content = match content_map.get(content_key) {
-            Some(c) => c.strip_metadata().to_string(),
-            None => {
-                //TODO: need to handle this better. But for now just merge all posts into a
-                //long markdown string and generate html
-                let keys = content_map.keys().filter(|x| x.starts_with(&format!("{file_name}/"))).collect::<Vec<&String>>();
-                let mut str = String::new();
-                for key in keys {
-                    let cont = match content_map.get(key) {
-                        Some(c) => c.strip_metadata(),
-                        None => String::default()
-                    };
-
-                    str.push_str(&cont);
-                    str.push_str("\n\n");
-                }
-                str
-            }
-        };
+    #[test]
+    fn build_with_missing_front_matter_layout_fails() {
+        let site_builder = SiteBuilder::new()
+            .with_nested_content(PathBuf::new(), "about", "+++\nlayout = \"missing\"\n+++\n");
+
+        let base_dir_path = site_builder.get_path();
 
-        let lexer = Lexer::new(&markdown_content).unwrap();
-        let mut html_generator = HtmlGenerator::new(lexer);
-        let html_content = html_generator.get_html().unwrap();
+        let result = build_internal(&base_dir_path, &BuildOptions::default());
 
-        let mut layout = merge_base_with_layout(&maybe_base_template, &value);
-        layout = layout.replace("{menu}", &menu_html);
-        let page = MergePage::parse(&layout, &html_content).expect("ERROR: Couldn't merge page");
-        let public_file = File::create(&public_dir_path.join(key)).expect(&format!("ERROR: Couldn't create page {key}"));
-        let mut buf_writer = BufWriter::new(public_file);
-        buf_writer.write(page.to_string().as_ref()).expect("ERROR: couldn't write content to file");
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(result.expect_err("ERROR: Build with missing layout succeeded"), "ERROR: about.md: layout \"missing.html\" doesn't exist");
     }
 
-    return HashMap::new();
-}
+    #[test]
+    fn build_with_menus_renders_configured_entries() {
+        let site_builder = SiteBuilder::new()
+            .with_config("[menu.main]\nSource = { url = \"https://example.com/src\", weight = 9 }\n")
+            .with_base_layout("base", "{{ menu }}|{% for entry in menus.footer %}{{ entry.name }}{% if entry.active %}*{% endif %}{% endfor %}|{{ layout }}")
+            .with_page_with_content("index", "{{ content }}", "+++\ntitle = \"Welcome\"\nmenu = { main = { name = \"Home\", weight = 1 } }\n+++\n")
+            .with_page_with_content("about", "{{ content }}", "+++\ntitle = \"About\"\nmenu = [\"main\", \"footer\"]\nweight = 2\n+++\n")
+            .with_page_with_content("hidden", "{{ content }}", "+++\ntitle = \"Hidden\"\n+++\n")
+            .with_nested_content(PathBuf::from("about"), "team", "+++\ntitle = \"Team\"\nmenu = { main = { parent = \"About\" } }\n+++\n");
 
-trait StringExt {
-    fn strip_metadata(&self) -> String;
-}
-impl StringExt for String {
-    fn strip_metadata(&self) -> String {
-        let lines = self.lines();
-        let mut in_metadata = false;
+        let base_dir_path = site_builder.get_path();
 
-        let mut line_vec = Vec::default();
-        for line in lines {
-            if line.starts_with("---") {
-                in_metadata = !in_metadata;
-                continue;
-            }
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-            if !in_metadata {
-                line_vec.push(line);
-            }
-        }
-        return line_vec.join("\n");
-    }
-}
+        let public_dir_path = base_dir_path.join("public");
+        let about_str = fs::read_to_string(public_dir_path.join("about.html")).expect("ERROR: Couldn't read about.html");
+        let team_str = fs::read_to_string(public_dir_path.join("about/team/index.html")).expect("ERROR: Couldn't read team");
 
-fn copy_resources_to_public(resources_dir: &PathBuf, public_dir: &PathBuf) {
-    if !Path::new(public_dir).exists() {
-        fs::create_dir_all(public_dir).expect("ERROR: Couldn't create public dir");
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(about_str, "<ul><li><a href=\"/index.html\">Home</a></li>\
+                               <li><a href=\"/about.html\" aria-current=\"page\">About</a><ul><li><a href=\"/about/team/\">Team</a></li></ul></li>\
+                               <li><a href=\"https://example.com/src\">Source</a></li></ul>|About*|");
+        assert!(team_str.contains("<a href=\"/about/team/\" aria-current=\"page\">Team</a>"), "Current page not marked: {team_str}");
+        assert!(!team_str.contains("Hidden"), "Page without menu is in the menu");
     }
-    for entry in fs::read_dir(resources_dir).expect("ERROR: Couldn't read resources dir") {
-        match entry {
-            Ok(entry) => {
-                let orig_path = entry.path();
-                let new_path = public_dir.join(&orig_path.file_name().expect("ERROR: Couldn't get file_name"));
-                fs::copy(orig_path, new_path).expect("ERROR: couldn't copy file");
-            },
-            Err(e) => {
-                eprintln!("ERROR: Couldn't read resource: {e}")
+
+    /// Every file under `dir` with its bytes.
+    fn read_dir_bytes(dir: &Path, base_dir: &Path, files: &mut BTreeMap<PathBuf, Vec<u8>>) {
+        for entry in fs::read_dir(dir).expect("ERROR: Couldn't read dir") {
+            let path = entry.expect("ERROR: Couldn't read dir entry").path();
+            match path.is_dir() {
+                true => read_dir_bytes(&path, base_dir, files),
+                false => {
+                    files.insert(get_relative_file_path(&path, base_dir), fs::read(&path).expect("ERROR: Couldn't read file"));
+                }
             }
         }
     }
-}
 
-fn merge_base_with_layout(base_tpl: &Option<String>, content_layout: &String) -> String {
-    let layout = match base_tpl {
-        Some(base) => {
-            let res = base.replace("{layout}", content_layout);
-            res.to_string()
-        },
-        None => {
-            content_layout.to_string()
+    #[test]
+    fn build_twice_writes_identical_output() {
+        let mut site_builder = SiteBuilder::new()
+            .with_config("title = \"Same\"\n[lists]\npage_size = 2\n[output]\ngemtext = true\n[params]\nb = 2\na = 1\n")
+            .with_base_layout("base", "{{ menu }}{% for section in site.sections %}{{ section.name }}:{{ section.pages | length }}{% endfor %}{% for param in site.params %}{{ param.key }}{% endfor %}{{ layout }}")
+            .with_resource("style", "css", "body {}");
+        for name in ["zeta", "alpha", "mid", "beta", "omega", "gamma"] {
+            site_builder = site_builder
+                .with_page_with_content(name, "{{ content }}{{ list }}{{ pagination }}", &format!("+++\ntitle = \"{name}\"\n+++\n# {name}"))
+                .with_nested_content(PathBuf::from(name), "one", "+++\ntitle = \"One\"\ndate = 2023-01-01\n+++\n")
+                .with_nested_content(PathBuf::from(name), "two", "+++\ntitle = \"Two\"\ndate = 2023-01-01\n+++\n")
+                .with_nested_content(PathBuf::from(name), "three", "+++\ntitle = \"Three\"\ndate = 2023-01-01\n+++\n");
         }
-    };
 
-    return layout;
-}
+        let base_dir_path = site_builder.get_path();
+        let public_dir_path = base_dir_path.join("public");
 
-static PUBLIC_DIR_PATH: &str = "public";
-static LAYOUT_DIR_PATH: &str = "layouts";
-static CONTENT_DIR_PATH: &str = "content";
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: First build failed");
+        let mut first = BTreeMap::new();
+        read_dir_bytes(&public_dir_path, &public_dir_path, &mut first);
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Second build failed");
+        let mut second = BTreeMap::new();
+        read_dir_bytes(&public_dir_path, &public_dir_path, &mut second);
 
-fn get_content(base_dir: &PathBuf) -> HashMap<String, String> {
-    let content_dir_path = base_dir.join(CONTENT_DIR_PATH);
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(first.len() > 30, "Too few files built: {}", first.len());
+        assert_eq!(first.keys().collect::<Vec<_>>(), second.keys().collect::<Vec<_>>());
+        for (path, bytes) in &first {
+            assert!(second[path] == *bytes, "{} differs between builds", path.display());
+        }
+    }
 
-    let map = get_content_rec(&content_dir_path, &content_dir_path);
-    return map;
-}
+    #[test]
+    fn build_options_from_args_reads_flags() {
+        let args: Vec<String> = ["afskylia", "build", "--drafts", "--future", "--expired", "--warn-only", "--config", "staging.toml"].iter().map(|x| x.to_string()).collect();
 
-//TODO: Should probably have a struct instead of string, to support ordering and custom naming
-fn build_menu_html(pages: Vec<String>) -> String {
-    let mut menu_html = String::from("<ul>");
-    for page in pages {
-        let page_name = page.replace(".html", "");
-        let menu_item = format!("<li><a href=\"/{page}\">{page_name}</a></li>");
-        menu_html.push_str(&menu_item);
+        assert_eq!(BuildOptions::from_args(&args), BuildOptions { drafts: true, future: true, expired: true, warn_only: true, config: Some(PathBuf::from("staging.toml")) });
+        assert_eq!(BuildOptions::from_args(&args[..2]), BuildOptions::default());
     }
-    menu_html.push_str("</ul>");
-    return menu_html;
-}
 
-fn get_content_rec(dir: &PathBuf, base_dir: &PathBuf) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    for path in fs::read_dir(&dir).expect("ERROR: Couldn't read content_dir_path") {
-        let path = path.expect("ERROR: couldn't get path").path();
-        if path.is_dir(){
-            let new_map = get_content_rec(&path.to_path_buf(), base_dir);
-            map.extend(new_map);
-            continue;
-        }
+    #[test]
+    fn held_back_reason_checks_publish_and_expiry_dates() {
+        let now = AfDateTime::from("2023-04-08T10:17:00Z");
+        let future = parse_content("---\npublishDate: 2023-05-01\n---\n", now.clone(), 0).expect("ERROR: Couldn't parse content").metadata;
+        let expired = parse_content("---\nexpiryDate: 2023-04-01\n---\n", now.clone(), 0).expect("ERROR: Couldn't parse content").metadata;
+        let current = parse_content("---\npublishDate: 2023-04-01\nexpiryDate: 2023-05-01\n---\n", now.clone(), 0).expect("ERROR: Couldn't parse content").metadata;
+
+        let options = BuildOptions::default();
+        assert_eq!(held_back_reason(&future, &options, &now), Some("scheduled for 2023-05-01T00:00:00".to_string()));
+        assert_eq!(held_back_reason(&expired, &options, &now), Some("expired 2023-04-01T00:00:00".to_string()));
+        assert_eq!(held_back_reason(&current, &options, &now), None);
+
+        let options = BuildOptions { future: true, expired: true, ..BuildOptions::default() };
+        assert_eq!(held_back_reason(&future, &options, &now), None);
+        assert_eq!(held_back_reason(&expired, &options, &now), None);
+    }
 
-        let file = File::open(&path).expect("ERROR: Couldn't open file");
-        let mut buf_reader = BufReader::new(file);
-                                                                                                                                                     
-        let mut content = String::new();
-        buf_reader.read_to_string(&mut content).expect("ERROR: Couldn't read file to string");
+    #[test]
+    fn parse_content_lastmod_from_front_matter_or_modified() {
+        let modified = AfDateTime::from("2023-04-08T10:17:00");
+        let with_lastmod = parse_content("+++\nlastmod = 2023-05-01\n+++\n", modified.clone(), 0).expect("ERROR: Couldn't parse content");
+        let without_lastmod = parse_content("# Hello", modified.clone(), 0).expect("ERROR: Couldn't parse content");
 
-        let relative_file_path = get_relative_file_path(&path, &base_dir);
-        map.insert(relative_file_path.into_os_string().into_string().expect("ERROR: Couldn't convert path to string"), content);
+        assert_eq!(with_lastmod.lastmod, AfDateTime::from("2023-05-01"));
+        assert_eq!(without_lastmod.lastmod, modified);
     }
-    return map.clone();
-}
 
-fn get_layouts(base_dir: &PathBuf) -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    let layouts_dir_path = base_dir.join(LAYOUT_DIR_PATH);
-
-    let paths = fs::read_dir(&layouts_dir_path).unwrap();
+    #[test]
+    fn get_git_lastmod_outside_repository_is_none() {
+        let site_builder = SiteBuilder::new()
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT);
+        let base_dir_path = site_builder.get_path();
 
-    for path in paths {
-        let path = path.unwrap().path();
-        let file = File::open(&path).expect("ERROR: Couldn't open file");
-        let mut buf_reader = BufReader::new(file);
-                                                                                                                                                     
-        let mut content = String::new();
-        buf_reader.read_to_string(&mut content).expect("ERROR: Couldn't read file to string");
+        let result = get_git_lastmod(&base_dir_path, &base_dir_path.join("content").join("index.md"));
 
-        let relative_file_path = get_relative_file_path(&path, &layouts_dir_path);
-        map.insert(relative_file_path.into_os_string().into_string().expect("ERROR: Couldn't convert path to string"), content);
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(result, None);
     }
 
-    return map;
-}
+    #[test]
+    fn build_writes_lastmod_to_template_and_sitemap() {
+        let page_content = "+++\n\
+                            lastmod = 2023-05-01T12:00:00+02:00\n\
+                            +++\n\
+                            # Hello";
+        let site_builder = SiteBuilder::new()
+            .with_config("base_url = \"https://example.com\"\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", "<p>Updated {lastmod}</p>{content}", page_content);
 
-fn get_relative_file_path(file_path: &PathBuf, base_path: &PathBuf) -> PathBuf {
-    let file_path = file_path.strip_prefix(base_path).expect("ERROR: Couldn't strip prefix");
-    return file_path.to_path_buf();
-}
+        let base_dir_path = site_builder.get_path();
 
-#[cfg(test)]
-mod tests {
-    use std::path::{Path, PathBuf};
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-    use claim::{assert_ok, assert_some};
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let sitemap_str = fs::read_to_string(public_dir_path.join("sitemap.xml")).expect("ERROR: Couldn't read sitemap.xml");
 
-    use crate::commands::test_utils::*;
-    use crate::commands::build::*;
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<p>Updated 2023-05-01T12:00:00+02:00</p>"), "Does not contain lastmod");
+        assert!(sitemap_str.contains("<loc>https://example.com/index.html</loc>\n    <lastmod>2023-05-01T12:00:00+02:00</lastmod>"), "Sitemap does not contain lastmod");
+    }
 
     #[test]
-    fn strip_metadata() {
-        let input = "---\n---\n# Hello".to_string();
-        let result = input.strip_metadata();
+    fn build_skips_future_page_unless_future_flag() {
+        let future_content = "---\n\
+                              publishDate: 2999-01-01\n\
+                              ---\n\
+                              # Later";
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT)
+            .with_page_with_content("later", INDEX_LAYOUT, future_content);
+
+        let base_dir_path = site_builder.get_path();
+        let public_dir_path = base_dir_path.join("public");
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+        let skipped = !public_dir_path.join("later.html").exists();
+        build_internal(&base_dir_path, &BuildOptions { future: true, ..BuildOptions::default() }).expect("ERROR: Build failed");
+        let rendered = public_dir_path.join("later.html").exists();
 
-        assert_eq!(result, "# Hello".to_string());
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(skipped, "Future page was rendered");
+        assert!(rendered, "Future page was not rendered with --future");
     }
+
     #[test]
     fn build_content_with_link_renders_a_tag() {
         let page_content = "# Index\n\
@@ -218,17 +1407,152 @@ mod tests {
 
         let base_dir_path = site_builder.get_path();
 
-        build_internal(&base_dir_path);
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-        let public_dir_path = base_dir_path.join(PUBLIC_DIR_PATH);
+        let public_dir_path = base_dir_path.join("public");
         let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
 
         println!("{index_file_str}");
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
-        assert_eq!(index_file_str.contains("<a href=\"https://google.com\">link</a>"), true, "Does not contain link");
+        assert!(index_file_str.contains("<a href=\"https://google.com\">link</a>"), "Does not contain link");
+
+    }
+    #[test]
+    fn build_empty_content_file_renders_layout() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, "");
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<body"), "Does not contain body-tag");
+    }
+
+    #[test]
+    fn build_with_config_disables_definition_lists() {
+        let page_content = "Term\n\
+                            : Definition";
+        let site_builder = SiteBuilder::new()
+            .with_config("[markdown]\ndefinition_lists = false\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, page_content);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(!index_file_str.contains("<dl>"), "Contains definition list");
+    }
+
+    #[test]
+    fn build_with_config_uses_site_settings_and_dirs() {
+        let site_builder = SiteBuilder::new()
+            .with_config("title = \"My Site\"\nbase_url = \"https://example.com/\"\n[dirs]\npublic = \"dist\"\n")
+            .with_base_layout("base", "<title>{site_title}</title>{layout}")
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("dist");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
+        let sitemap_str = fs::read_to_string(public_dir_path.join("sitemap.xml")).expect("ERROR: Couldn't read sitemap.xml");
+        let default_public_exists = base_dir_path.join("public").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_file_str.contains("<title>My Site</title>"), "Does not contain site title");
+        assert!(sitemap_str.contains("<loc>https://example.com/index.html</loc>"), "Sitemap loc is not absolute");
+        assert!(!default_public_exists, "Wrote to the default public dir");
+    }
+
+    #[test]
+    fn build_without_base_url_skips_sitemap() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let index_exists = base_dir_path.join("public/index.html").exists();
+        let sitemap_exists = base_dir_path.join("public/sitemap.xml").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(index_exists, "Site wasn't built");
+        assert!(!sitemap_exists, "Wrote a sitemap with relative urls");
+    }
+
+    #[test]
+    fn build_escapes_sitemap_locations() {
+        let site_builder = SiteBuilder::new()
+            .with_config("base_url = \"https://ex.com/a?x=1&y=2\"\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT)
+            .with_page_with_content("q&a", INDEX_LAYOUT, INDEX_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let sitemap_str = fs::read_to_string(base_dir_path.join("public/sitemap.xml")).expect("ERROR: Couldn't read sitemap.xml");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(sitemap_str.contains("<loc>https://ex.com/a?x=1&amp;y=2/index.html</loc>"), "Sitemap loc is not escaped: {sitemap_str}");
+        assert!(sitemap_str.contains("<loc>https://ex.com/a?x=1&amp;y=2/q&amp;a.html</loc>"), "Sitemap loc is not escaped: {sitemap_str}");
+        assert_eq!(sitemap_str.matches('&').count(), sitemap_str.matches("&amp;").count(), "Sitemap contains a raw &");
+    }
+
+    #[test]
+    fn build_gemtext_only_changes_file_extension() {
+        let site_builder = SiteBuilder::new()
+            .with_config("[output]\ngemtext = true\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "post-1", POST_1_CONTENT)
+            .with_nested_content(PathBuf::from("posts/docs.html"), "page", POST_2_CONTENT);
+
+        let base_dir_path = site_builder.get_path();
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let gmi_exists = base_dir_path.join("public/posts/docs.html/page/index.gmi").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(gmi_exists, "Gemtext page was written to the wrong path");
+    }
+
+    #[test]
+    fn build_with_gemtext_output_writes_gmi() {
+        let page_content = "# Index\n\
+                            [link](https://google.com)";
+        let site_builder = SiteBuilder::new()
+            .with_config("[output]\ngemtext = true\n")
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, page_content);
+
+        let base_dir_path = site_builder.get_path();
 
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
+
+        let public_dir_path = base_dir_path.join("public");
+        let index_file_str = fs::read_to_string(public_dir_path.join("index.gmi")).expect("ERROR: Couldn't read index.gmi");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(index_file_str, "# Index\n\n=> https://google.com link\n\n");
     }
+
     #[test]
     fn copy_resources_copies_resources() {
         let site_builder = SiteBuilder::new()
@@ -239,17 +1563,71 @@ mod tests {
 
         let public_dir_path = base_dir_path.join("public");
 
-        copy_resources_to_public(&base_dir_path.join("resources"), &public_dir_path);
+        copy_resources_to_public(&base_dir_path.join("resources"), &public_dir_path, &SiteConfig::default());
+
+        assert!(Path::new(&public_dir_path.join("style.css")).exists(), "style.css doesn't exist");
+        assert!(Path::new(&public_dir_path.join("script.js")).exists(), "script.js doesn't exist");
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+    }
+
+    #[test]
+    fn copy_resources_copies_nested_resources_and_skips_swap_files() {
+        let site_builder = SiteBuilder::new()
+            .with_resource("style", "css", "")
+            .with_file("resources/img/icons/logo.png", &[0x89, 0x50, 0x4e, 0x47, 0xff])
+            .with_file("resources/.style.css.swp", b"swap");
+
+        let base_dir_path = site_builder.get_path();
+        let public_dir_path = base_dir_path.join("public");
+
+        copy_resources_to_public(&base_dir_path.join("resources"), &public_dir_path, &SiteConfig::default());
+        copy_resources_to_public(&base_dir_path.join("missing"), &public_dir_path, &SiteConfig::default());
+
+        let logo = fs::read(public_dir_path.join("img/icons/logo.png")).expect("ERROR: Couldn't read logo.png");
+        let swap_exists = public_dir_path.join(".style.css.swp").exists();
+        let style_exists = public_dir_path.join("style.css").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_eq!(logo, vec![0x89, 0x50, 0x4e, 0x47, 0xff]);
+        assert!(style_exists, "style.css doesn't exist");
+        assert!(!swap_exists, "Swap file was copied");
+    }
+
+    #[test]
+    fn build_copies_page_bundle_assets_next_to_page() {
+        let site_builder = SiteBuilder::new()
+            .with_nested_content(PathBuf::from("posts"), "hello", "+++\ntitle = \"Hello\"\nslug = \"hi\"\n+++\n![Cover](cover.png)")
+            .with_file("content/posts/hello/cover.png", &[0xff, 0xd8])
+            .with_file("content/posts/hello/img/wide.png", &[0xff])
+            .with_nested_content(PathBuf::from("posts"), "secret", "---\npublished: false\n---\n")
+            .with_file("content/posts/secret/plan.png", &[0xff])
+            .with_file("content/files/report.pdf", &[0x25, 0x50, 0x44, 0x46])
+            .with_file("content/posts/.hello.md.swp", &[0xff, 0xfe]);
+
+        let base_dir_path = site_builder.get_path();
+        fs::remove_dir_all(base_dir_path.join("resources")).expect("ERROR: Couldn't remove resources dir");
+
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-        assert_eq!(Path::new(&public_dir_path.join("style.css")).exists(), true, "style.css doesn't exist");
-        assert_eq!(Path::new(&public_dir_path.join("script.js")).exists(), true, "script.js doesn't exist");
+        let public_dir_path = base_dir_path.join("public");
+        let cover_exists = public_dir_path.join("posts/hi/cover.png").exists();
+        let nested_exists = public_dir_path.join("posts/hi/img/wide.png").exists();
+        let report_exists = public_dir_path.join("files/report.pdf").exists();
+        let secret_exists = public_dir_path.join("posts/secret/plan.png").exists();
+        let swap_exists = public_dir_path.join("posts/.hello.md.swp").exists();
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert!(cover_exists, "Bundle asset isn't next to the page");
+        assert!(nested_exists, "Nested bundle asset isn't next to the page");
+        assert!(report_exists, "Content file outside a bundle wasn't copied");
+        assert!(!secret_exists, "Held back page's asset was copied");
+        assert!(!swap_exists, "Swap file was copied");
     }
 
     #[test]
     fn build_menu_creates_menu_html() {
-        let menu_items: Vec<String> = vec!["index.html", "second-page.html", "posts.html"].iter().map(|x| x.to_string()).collect();
+        let menu_items: Vec<String> = ["index.html", "second-page.html", "posts.html"].iter().map(|x| x.to_string()).collect();
         let expected = "<ul><li><a href=\"/index.html\">index</a></li><li><a href=\"/second-page.html\">second-page</a></li><li><a href=\"/posts.html\">posts</a></li></ul>";
 
         let result = build_menu_html(menu_items);
@@ -263,16 +1641,16 @@ mod tests {
             .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT);
         let base_dir_path = site_builder.get_path();
 
-        build_internal(&base_dir_path);
+        build_internal(&base_dir_path, &BuildOptions::default()).expect("ERROR: Build failed");
 
-        let public_dir_path = base_dir_path.join(PUBLIC_DIR_PATH);
+        let public_dir_path = base_dir_path.join("public");
         let index_file_str = fs::read_to_string(public_dir_path.join("index.html")).expect("ERROR: Couldn't read index.html");
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
-        assert_eq!(index_file_str.contains("<h1"), true, "Does not contain h1");
-        assert_eq!(index_file_str.contains("<html"), true, "Does not contain html-tag");
-        assert_eq!(index_file_str.contains("<head>"), true, "Does not contain head-tag");
-        assert_eq!(index_file_str.contains("<body"), true, "Does not contain body-tag");
+        assert!(index_file_str.contains("<h1"), "Does not contain h1");
+        assert!(index_file_str.contains("<html"), "Does not contain html-tag");
+        assert!(index_file_str.contains("<head>"), "Does not contain head-tag");
+        assert!(index_file_str.contains("<body"), "Does not contain body-tag");
     }
 
     #[test]
@@ -284,7 +1662,7 @@ mod tests {
             .with_nested_content(PathBuf::from("posts"), "post-2", POST_2_CONTENT);
         let base_dir_path = site_builder.get_path();
 
-        let content_map = get_content(&base_dir_path);
+        let content_map = get_content(&base_dir_path.join("content"), &SiteConfig::default());
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
         assert_some!(content_map.get("index.md"), "Couldn't get index.md");
@@ -299,25 +1677,48 @@ mod tests {
             .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT);
         let base_dir_path = site_builder.get_path();
 
-        let content_map = get_content(&base_dir_path);
+        let content_map = get_content(&base_dir_path.join("content"), &SiteConfig::default());
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
         assert_some!(content_map.get("index.md"), "Couldn't get index.md");
     }
 
+    #[test]
+    fn build_ignores_non_html_layouts() {
+        let site_builder = SiteBuilder::new()
+            .with_base_layout("base", BASE)
+            .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT)
+            .with_layout("a.js", "let a;")
+            .with_layout("style.css", "body {}");
+
+        let base_dir_path = site_builder.get_path();
+
+        let result = build_internal(&base_dir_path, &BuildOptions::default());
+        let index_exists = base_dir_path.join("public/index.html").exists();
+
+        assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
+        assert_ok!(result);
+        assert!(index_exists, "index.html wasn't built");
+    }
+
     #[test]
     fn get_layouts_returns_layouts() {
         let site_builder = SiteBuilder::new()
             .with_base_layout("base", BASE)
             .with_page_with_content("index", INDEX_LAYOUT, INDEX_CONTENT)
-            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "post-1", POST_1_CONTENT);
+            .with_page_with_nested_content("posts", INDEX_LAYOUT, PathBuf::from("posts"), "post-1", POST_1_CONTENT)
+            .with_partial("nav", "<nav></nav>")
+            .with_layout("a.js", "let a;")
+            .with_layout("notes.txt", "notes");
 
         let base_dir_path = site_builder.get_path();
-        let layout_map = get_layouts(&base_dir_path);
+        let layout_map = get_layouts(&base_dir_path.join("layouts"), &SiteConfig::default());
 
         assert_ok!(fs::remove_dir_all(base_dir_path.as_path()));
         assert_some!(layout_map.get("index.html"));
         assert_some!(layout_map.get("posts.html"));
+        assert_some!(layout_map.get("partials/nav.html"));
+        assert_eq!(layout_map.len(), 4);
     }
 
 
@@ -354,6 +1755,12 @@ pub mod test_utils {
         base_dir_path: PathBuf
     }
 
+    impl Default for SiteBuilder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl SiteBuilder {
         pub fn new() -> Self {
             let uuid = Uuid::new_v4().to_string();
@@ -370,11 +1777,49 @@ pub mod test_utils {
             return self.base_dir_path.clone();
         }
 
+        pub fn with_config(self, config_content: &str) -> Self {
+            let config_file = File::create(self.base_dir_path.join("afskylia.toml")).expect("ERROR: couldn't create config file");
+            let mut buf_writer = BufWriter::new(config_file);
+            buf_writer.write_all(config_content.as_ref()).expect("ERROR: couldn't write to config file");
+
+            return self;
+        }
+
+        pub fn with_schema(self, schema_content: &str) -> Self {
+            fs::write(self.base_dir_path.join("schema.toml"), schema_content).expect("ERROR: couldn't write schema file");
+
+            return self;
+        }
+
         pub fn with_base_layout(self, layout_name: &str, layout_content: &str) -> Self {
             let layout_file_name = format!("_{layout_name}.html");
             let base_layout_file = File::create(self.base_dir_path.join("layouts").join(layout_file_name)).expect("ERROR: couldn't create base layout file");
             let mut buf_writer = BufWriter::new(base_layout_file);
-            buf_writer.write(layout_content.as_ref()).expect("ERROR: couldn't write to content file");
+            buf_writer.write_all(layout_content.as_ref()).expect("ERROR: couldn't write to content file");
+
+            return self;
+        }
+
+        pub fn with_layout(self, layout_path: &str, layout_content: &str) -> Self {
+            let layout_file_path = self.base_dir_path.join("layouts").join(layout_path);
+            fs::create_dir_all(layout_file_path.parent().expect("ERROR: Layout has no parent dir")).expect("ERROR: Couldn't create layout dir");
+            fs::write(layout_file_path, layout_content).expect("ERROR: couldn't write layout file");
+
+            return self;
+        }
+
+        pub fn with_partial(self, partial_name: &str, partial_content: &str) -> Self {
+            let partials_dir_path = self.base_dir_path.join("layouts").join("partials");
+            fs::create_dir_all(&partials_dir_path).expect("ERROR: Couldn't create partials dir");
+            fs::write(partials_dir_path.join(format!("{partial_name}.html")), partial_content).expect("ERROR: couldn't write partial file");
+
+            return self;
+        }
+
+        pub fn with_file(self, file_path: &str, file_content: &[u8]) -> Self {
+            let file_path = self.base_dir_path.join(file_path);
+            fs::create_dir_all(file_path.parent().expect("ERROR: File has no parent dir")).expect("ERROR: Couldn't create file dir");
+            fs::write(file_path, file_content).expect("ERROR: couldn't write file");
 
             return self;
         }
@@ -383,7 +1828,7 @@ pub mod test_utils {
             let resource_file_name = format!("{}.{}", resource_name, resource_file_extension);
             let resource_file = File::create(self.base_dir_path.join("resources").join(resource_file_name)).expect("ERROR: couldn't create resources file");
             let mut buf_writer = BufWriter::new(resource_file);
-            buf_writer.write(resource_content.as_ref()).expect("ERROR: couldn't write to resource file");
+            buf_writer.write_all(resource_content.as_ref()).expect("ERROR: couldn't write to resource file");
             return self;
         }
 
@@ -392,13 +1837,13 @@ pub mod test_utils {
             let layout_file_name = format!("{}.html", page_name);
             let layout_file = File::create(self.base_dir_path.join("layouts").join(layout_file_name)).expect("ERROR: couldn't create layout file");
             let mut buf_writer = BufWriter::new(layout_file);
-            buf_writer.write(page_layout.as_ref()).expect("ERROR: couldn't write to layout file");
+            buf_writer.write_all(page_layout.as_ref()).expect("ERROR: couldn't write to layout file");
 
             //content
             let content_file_name = format!("{}.md", page_name);
             let content_file = File::create(self.base_dir_path.join("content").join(content_file_name)).expect("Error: couldn't content file");
             buf_writer = BufWriter::new(content_file);
-            buf_writer.write(page_content.as_ref()).expect("ERROR: couldn't write to content file");
+            buf_writer.write_all(page_content.as_ref()).expect("ERROR: couldn't write to content file");
             return self;
         }
 
@@ -409,21 +1854,22 @@ pub mod test_utils {
             let layout_file_name = format!("{}.html", page_name);
             let layout_file = File::create(self.base_dir_path.join("layouts").join(layout_file_name)).expect("ERROR: couldn't create layout file");
             let mut buf_writer = BufWriter::new(layout_file);
-            buf_writer.write(page_layout.as_ref()).expect("ERROR: couldn't write to layout file");
+            buf_writer.write_all(page_layout.as_ref()).expect("ERROR: couldn't write to layout file");
 
             //content
             let content_file_name = format!("{}.md", page_content_name);
             let content_file = File::create(self.base_dir_path.join("content").join(page_content_path).join(content_file_name)).expect("Error: couldn't create content file");
             buf_writer = BufWriter::new(content_file);
-            buf_writer.write(page_content.as_ref()).expect("ERROR: couldn't write to content file");
+            buf_writer.write_all(page_content.as_ref()).expect("ERROR: couldn't write to content file");
             return self;
         }
 
         pub fn with_nested_content(self, content_path: PathBuf, content_name: &str, content: &str) -> Self {
+            fs::create_dir_all(self.base_dir_path.join("content").join(&content_path)).expect("ERROR: Couldn't create content_path");
             let content_file_name = format!("{}.md", content_name);
             let content_file = File::create(self.base_dir_path.join("content").join(&content_path).join(content_file_name)).expect("Error: couldn't create content file");
             let mut buf_writer = BufWriter::new(content_file);
-            buf_writer.write(content.as_ref()).expect("ERROR: couldn't write to content file");
+            buf_writer.write_all(content.as_ref()).expect("ERROR: couldn't write to content file");
             return self;
         }
     }
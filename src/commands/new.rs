@@ -1,46 +1,61 @@
-use std::{path::Path, env::current_dir, fs::{self, File}, io::{Error, BufWriter, Write}};
+use std::{path::Path, env::current_dir, fs::{self, File}, io::{Error, ErrorKind, BufWriter, Write}};
 
-pub fn new(args: &Vec<String>) {
+use crate::commands::BuildOptions;
+use crate::config::{SiteConfig, CONFIG_FILE_NAME};
+
+pub fn new(args: &[String]) {
     let command = &args[2];
     // let command = args.iter().nth(2);
     match command.as_str() {
         "help" => println!("{}", HELP),
-        "site" => new_site(),
+        "site" => new_site(BuildOptions::from_args(&args[1..]).config.as_deref()),
         "page" => new_page(&args[3]),
         _ => println!("Unknown parameter '{}'", command.as_str()),
     }
 }
 
-fn new_site() {
+fn new_site(config_file: Option<&Path>) {
     let dir = current_dir().unwrap();
-    new_site_internal(dir.as_path()).unwrap();
+    new_site_internal(dir.as_path(), config_file).unwrap();
 }
 
-fn new_site_internal(base_dir: &Path) -> Result<(), Error> {
+fn new_site_internal(base_dir: &Path, config_file: Option<&Path>) -> Result<(), Error> {
     println!("create new site at {}", base_dir.display());
 
-    for dir in get_site_dirs() {
+    // Nothing is written into an existing site, least of all its config
+    let config_path = base_dir.join(config_file.unwrap_or(Path::new(CONFIG_FILE_NAME)));
+    let config = SiteConfig::parse(CONFIG).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let site_dirs = get_site_dirs(&config);
+    for path in [config_path.clone()].into_iter().chain(site_dirs.iter().map(|x| base_dir.join(x))) {
+        if path.exists() {
+            return Err(Error::new(ErrorKind::AlreadyExists, format!("{} already exists", path.display())));
+        }
+    }
+
+    fs::write(&config_path, CONFIG)?;
+    for dir in site_dirs {
         fs::create_dir(base_dir.join(dir).as_path())?;
     }
 
-    let base_layout_file = File::create(base_dir.join("layouts").join("_base.html")).expect("ERROR: couldn't create base layout file");
+    let layouts_dir = base_dir.join(&config.dirs.layouts);
+    let base_layout_file = File::create(layouts_dir.join("_base.html")).expect("ERROR: couldn't create base layout file");
     let mut buf_writer = BufWriter::new(base_layout_file);
-    buf_writer.write(BASE.as_ref()).expect("ERROR: couldn't write to layout file");
+    buf_writer.write_all(BASE.as_ref()).expect("ERROR: couldn't write to layout file");
 
 
-    let index_layout_file = File::create(base_dir.join("layouts").join("index.html")).expect("ERROR: couldn't create index layout file");
+    let index_layout_file = File::create(layouts_dir.join("index.html")).expect("ERROR: couldn't create index layout file");
     buf_writer = BufWriter::new(index_layout_file);
-    buf_writer.write(INDEX_LAYOUT.as_ref()).expect("ERROR: couldn't write to layout file");
+    buf_writer.write_all(INDEX_LAYOUT.as_ref()).expect("ERROR: couldn't write to layout file");
 
 
-    let index_content_file = File::create(base_dir.join("content").join("index.md")).expect("ERROR: couldn't create index content file");
+    let index_content_file = File::create(base_dir.join(&config.dirs.content).join("index.md")).expect("ERROR: couldn't create index content file");
     buf_writer = BufWriter::new(index_content_file);
-    buf_writer.write(INDEX_CONTENT.as_ref()).expect("ERROR: couldn't write to content file");
+    buf_writer.write_all(INDEX_CONTENT.as_ref()).expect("ERROR: couldn't write to content file");
     return Ok(());
 }
 
-fn get_site_dirs() -> Vec<String> {
-    let dirs = vec!["content", "layouts", "resources"];
+fn get_site_dirs(config: &SiteConfig) -> Vec<String> {
+    let dirs = [&config.dirs.content, &config.dirs.layouts, &config.dirs.resources];
     return dirs.iter().map(|s| s.to_string()).collect();
 }
 
@@ -48,15 +63,24 @@ fn new_page(_page_name: &str) {
     println!("create page {}", _page_name);
 }
 
+static CONFIG: &str = r#"title = "My New Site"
+base_url = "https://example.org/"
+language = "en"
+
+[server]
+host = "127.0.0.1"
+port = 1313
+"#;
+
 static BASE: &str = r#"<!DOCTYPE html>
-<html lang="en">
+<html lang="{{ site.language }}">
 <head>
 <meta charset="utf-8">
-<title>My Page</title>
+<title>{{ page.title }} | {{ site.title }}</title>
 </head>
 <body>
-{menu}
-{layout}
+{{ menu }}
+{{ layout }}
 </body>
 </html>
 "#;
@@ -72,7 +96,7 @@ Some content
 "#;
 
 static INDEX_LAYOUT: &str = r#"
-{content}
+{{ content }}
 "#;
 
 static HELP: &str = r#"
@@ -85,6 +109,9 @@ Available Commands:
 site        Create a new site 
 page        Create a new page
 
+Flags:
+--config    Use this config file instead of afskylia.toml
+
 "#;
 
 #[cfg(test)]
@@ -95,6 +122,8 @@ mod tests {
     use claim::assert_ok;
     use uuid::Uuid;
 
+    use crate::config::SiteConfig;
+
     use super::{new_site_internal, get_site_dirs};
 
     #[test]
@@ -103,11 +132,12 @@ mod tests {
         let base_dir = temp_dir().join("rust").join(uuid);
         fs::create_dir_all(base_dir.as_path()).expect("ERROR: couldn't create base_dir");
 
-        new_site_internal(base_dir.as_path())?;
+        new_site_internal(base_dir.as_path(), None)?;
 
-        for d in get_site_dirs() {
+        assert_ok!(fs::metadata(base_dir.join("afskylia.toml")));
+        for d in get_site_dirs(&SiteConfig::default()) {
             let dir_meta = fs::metadata(base_dir.join(&d))?;
-            assert_eq!(dir_meta.is_dir(), true, "{} is not a directory", d);
+            assert!(dir_meta.is_dir(), "{} is not a directory", d);
         }
 
         let mut base_metadata = fs::metadata(base_dir.join("layouts/_base.html"));
@@ -122,6 +152,27 @@ mod tests {
         return Ok(());
     }
 
+    #[test]
+    fn test_new_site_internal_keeps_existing_site() -> Result<(), Error> {
+        let uuid = Uuid::new_v4().to_string();
+        let base_dir = temp_dir().join("rust").join(uuid);
+        fs::create_dir_all(base_dir.join("content").as_path()).expect("ERROR: couldn't create base_dir");
+        fs::write(base_dir.join("afskylia.toml"), "title = \"Mine\"\n")?;
+
+        let result = new_site_internal(base_dir.as_path(), None);
+        let config = fs::read_to_string(base_dir.join("afskylia.toml"))?;
+        fs::remove_file(base_dir.join("afskylia.toml"))?;
+        let dir_result = new_site_internal(base_dir.as_path(), None);
+        let config_written = base_dir.join("afskylia.toml").exists();
+
+        fs::remove_dir_all(base_dir.as_path())?;
+        assert!(result.is_err(), "Created a site over an existing one");
+        assert_eq!(config, "title = \"Mine\"\n");
+        assert!(dir_result.is_err(), "Created a site over an existing content dir");
+        assert!(!config_written, "Wrote a config before failing");
+        return Ok(());
+    }
+
     #[test]
     fn test_tmp_dir() -> Result<(), Error> {
         let uuid = Uuid::new_v4().to_string();
@@ -135,10 +186,10 @@ mod tests {
 
         let file_meta = fs::metadata(file_path)?;
         let dir_meta = fs::metadata(dir_path)?;
-        assert_eq!(file_meta.is_file(), true);
-        assert_eq!(file_meta.is_dir(), false);
-        assert_eq!(dir_meta.is_dir(), true);
-        assert_eq!(dir_meta.is_file(), false);
+        assert!(file_meta.is_file());
+        assert!(!file_meta.is_dir());
+        assert!(dir_meta.is_dir());
+        assert!(!dir_meta.is_file());
         fs::remove_dir_all(base_dir.as_path())?;
         return Ok(());
     }
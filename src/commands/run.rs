@@ -1,41 +1,49 @@
-use std::{collections::HashMap, env::current_dir, path::PathBuf, fs::{self, File}, io::{BufReader, Read}};
+use std::{collections::HashMap, env::current_dir, path::{Path, PathBuf}, fs::{self, File}, io::{BufReader, Read}};
 
+use crate::config::SiteConfig;
 use crate::server;
-use crate::commands::build;
+use crate::commands::{BuildOptions, build_site};
 
-pub fn run(args: &Vec<String>) {
-    build(args);
+pub fn run(args: &[String]) {
+    let options = BuildOptions::from_args(args);
+    build_site(&options);
     let curr_dir_path = current_dir().unwrap();
+    let config = SiteConfig::load(&curr_dir_path, options.config.as_deref()).expect("ERROR: Couldn't load site config");
 
-    let public_folder_path = curr_dir_path.join(PUBLIC_DIR_PATH);
+    let public_folder_path = curr_dir_path.join(&config.dirs.public);
 
     let map = get_folder_contents(public_folder_path);
 
-    server::start(&map);
+    server::start(&config.server.host, config.server.port, &map);
 }
 
-static PUBLIC_DIR_PATH: &str = "public";
-
 fn get_folder_contents(public_folder_path: PathBuf) -> HashMap<String, Vec<u8>> {
+    return get_folder_contents_rec(&public_folder_path, &public_folder_path);
+}
+
+fn get_folder_contents_rec(dir: &Path, public_folder_path: &Path) -> HashMap<String, Vec<u8>> {
     let mut map = HashMap::new();
-    let dir = fs::read_dir(&public_folder_path).expect("ERROR: Can't read directory");
-    for path in dir {
+    for path in fs::read_dir(dir).expect("ERROR: Can't read directory") {
         let path = path.expect("ERROR: Something went wrong with path").path();
+        if path.is_dir() {
+            map.extend(get_folder_contents_rec(&path, public_folder_path));
+            continue;
+        }
 
         let file = File::open(&path).expect("ERROR: Couldn't open file");
         let mut buf_reader = BufReader::new(file);
-                                                                                                                                                     
-        let mut content = String::new();
-        buf_reader.read_to_string(&mut content).expect("ERROR: Couldn't read file to string");
 
-        let relative_file_path = get_relative_file_path(&path, &public_folder_path).into_os_string().into_string().expect("ERROR: COuldn't convert path to string");
+        let mut content = Vec::new();
+        buf_reader.read_to_end(&mut content).expect("ERROR: Couldn't read file");
+
+        let relative_file_path = get_relative_file_path(&path, public_folder_path).into_os_string().into_string().expect("ERROR: COuldn't convert path to string");
         let relative_file_path_string = format!("/{}", relative_file_path);
-        map.insert(relative_file_path_string, content.as_bytes().to_vec());
+        map.insert(relative_file_path_string, content);
     }
     return map;
 }
 
-fn get_relative_file_path(file_path: &PathBuf, base_path: &PathBuf) -> PathBuf {
+fn get_relative_file_path(file_path: &Path, base_path: &Path) -> PathBuf {
     let file_path = file_path.strip_prefix(base_path).expect("ERROR: Couldn't strip prefix");
     return file_path.to_path_buf();
 }
@@ -0,0 +1,5 @@
+mod schema;
+mod site_config;
+
+pub use schema::{Schema, SCHEMA_FILE_NAME};
+pub use site_config::{SiteConfig, Dirs, ServerConfig, ListConfig, MenuLink, CONFIG_FILE_NAME, parse_sort_order};
@@ -0,0 +1,267 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use crate::parsers::metadata::{AfDateTime, Metadata, Value};
+
+pub static SCHEMA_FILE_NAME: &str = "schema.toml";
+
+/// The front matter types a schema can ask for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldType {
+    String,
+    Date,
+    Bool,
+    Integer,
+    Float,
+    List,
+    Table,
+    Any,
+}
+
+impl FieldType {
+    fn parse(input: &str) -> Option<FieldType> {
+        return match input {
+            "string" => Some(FieldType::String),
+            "date" => Some(FieldType::Date),
+            "bool" => Some(FieldType::Bool),
+            "integer" => Some(FieldType::Integer),
+            "float" => Some(FieldType::Float),
+            "list" => Some(FieldType::List),
+            "table" => Some(FieldType::Table),
+            "any" => Some(FieldType::Any),
+            _ => None,
+        };
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FieldType::String => "string",
+            FieldType::Date => "date",
+            FieldType::Bool => "bool",
+            FieldType::Integer => "integer",
+            FieldType::Float => "float",
+            FieldType::List => "list",
+            FieldType::Table => "table",
+            FieldType::Any => "any",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub key: String,
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+/// The fields for the pages in one content section, e.g. `[posts]` or
+/// `[posts/*]` for everything under `content/posts/`. `[*]` applies to every
+/// page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSchema {
+    pub section: String,
+    pub fields: Vec<Field>,
+}
+
+/// Front matter rules from `schema.toml` at the site root:
+///
+/// ```toml
+/// [posts]
+/// title = "string"
+/// date = "date"
+/// tags = "list"
+/// hero = "string?"
+/// ```
+///
+/// A `?` after the type makes the key optional, but still type checked.
+#[derive(Debug, Default, PartialEq)]
+pub struct Schema {
+    pub sections: Vec<SectionSchema>,
+}
+
+impl Schema {
+    /// Reads `schema.toml` from the site root. A site without one has no
+    /// schema and isn't validated.
+    pub fn load(base_dir: &Path) -> Result<Option<Schema>, String> {
+        let schema_path = base_dir.join(SCHEMA_FILE_NAME);
+        if !schema_path.is_file() {
+            return Ok(None);
+        }
+
+        let input = fs::read_to_string(&schema_path).map_err(|e| format!("ERROR: Couldn't read {}: {e}", schema_path.display()))?;
+        return Schema::parse(&input).map(Some);
+    }
+
+    pub fn parse(input: &str) -> Result<Schema, String> {
+        let mut schema = Schema::default();
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let section = line[1..line.len()-1].trim().trim_matches('"');
+                let section = section.strip_suffix("/*").unwrap_or(section).trim_matches('/').to_string();
+                schema.sections.push(SectionSchema { section, fields: Vec::new() });
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim().trim_matches('"'), value.trim().trim_matches('"')),
+                None => return Err(format!("ERROR: Expected key = \"type\" on line {}", i+1)),
+            };
+            let Some(section) = schema.sections.last_mut() else {
+                return Err(format!("ERROR: Key {key:?} on line {} is not in a [section]", i+1));
+            };
+
+            let (type_name, required) = match value.strip_suffix('?') {
+                Some(type_name) => (type_name, false),
+                None => (value, true),
+            };
+            let field_type = FieldType::parse(type_name).ok_or(format!("ERROR: Unknown type {type_name:?} on line {}", i+1))?;
+            section.fields.push(Field { key: key.to_string(), field_type, required });
+        }
+
+        return Ok(schema);
+    }
+
+    /// Makes sure every section names a dir in the content dir, so a typo
+    /// doesn't silently turn its rules off.
+    pub fn check_sections(&self, content_dir: &Path) -> Result<(), String> {
+        for section in &self.sections {
+            if section.section != "*" && !content_dir.join(&section.section).is_dir() {
+                return Err(format!("ERROR: Section [{}] in {SCHEMA_FILE_NAME} matches no dir in {}", section.section, content_dir.display()));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Checks a page against every section it belongs to. `content_path` is
+    /// relative to the content dir, e.g. `posts/hello.md`.
+    pub fn validate(&self, content_path: &str, metadata: &Metadata) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        let sections = self.sections.iter()
+            .filter(|x| x.section == "*" || content_path.starts_with(&format!("{}/", x.section)));
+        for section in sections {
+            for field in &section.fields {
+                if !metadata.has_key(&field.key) {
+                    if field.required {
+                        violations.push(format!("missing required key \"{}\"", field.key));
+                    }
+                    continue;
+                }
+
+                if !has_type(metadata, &field.key, field.field_type) {
+                    violations.push(format!("key \"{}\" should be {}", field.key, field.field_type));
+                }
+            }
+        }
+
+        return violations;
+    }
+}
+
+/// Known keys have the type the front matter parser already enforced, the
+/// rest are checked by their value.
+fn has_type(metadata: &Metadata, key: &str, field_type: FieldType) -> bool {
+    if field_type == FieldType::Any {
+        return true;
+    }
+
+    let known_type = match key {
+        "date" | "publishDate" | "expiryDate" | "lastmod" => Some(FieldType::Date),
+        "published" => Some(FieldType::Bool),
+        "title" | "description" | "slug" | "author" => Some(FieldType::String),
+        "tags" | "categories" | "aliases" => Some(FieldType::List),
+        "weight" => Some(FieldType::Integer),
+        _ => None,
+    };
+    if let Some(known_type) = known_type {
+        return known_type == field_type;
+    }
+
+    return match (metadata.get_param(key), field_type) {
+        (Some(Value::String(_)), FieldType::String) => true,
+        (Some(Value::String(s)), FieldType::Date) => AfDateTime::parse(s).is_ok(),
+        (Some(Value::Boolean(_)), FieldType::Bool) => true,
+        (Some(Value::Integer(_)), FieldType::Integer | FieldType::Float) => true,
+        (Some(Value::Float(_)), FieldType::Float) => true,
+        (Some(Value::Array(_)), FieldType::List) => true,
+        (Some(Value::Table(_)), FieldType::Table) => true,
+        _ => false,
+    };
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use claim::assert_err;
+
+    use crate::parsers::metadata::{AfDateTime, Metadata};
+
+    use super::{Field, FieldType, Schema};
+
+    static SCHEMA: &str = "# Front matter rules\n\
+                           [posts]\n\
+                           title = \"string\"\n\
+                           date = \"date\"\n\
+                           tags = \"list\"\n\
+                           rating = \"float?\"\n\
+                           \n\
+                           [*]\n\
+                           description = \"string?\"\n";
+
+    fn metadata(input: &str) -> Metadata {
+        return Metadata::parse(input, AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
+    }
+
+    #[test]
+    fn parse_schema() {
+        let schema = Schema::parse(SCHEMA).expect("ERROR: Couldn't parse schema");
+
+        assert_eq!(schema.sections.len(), 2);
+        assert_eq!(schema.sections[0].section, "posts");
+        assert_eq!(schema.sections[0].fields[3], Field { key: "rating".to_string(), field_type: FieldType::Float, required: false });
+        assert_err!(Schema::parse("[posts]\ntitle = \"text\"\n"));
+        assert_err!(Schema::parse("title = \"string\"\n"));
+    }
+
+    #[test]
+    fn validate_reports_missing_keys_and_wrong_types() {
+        let schema = Schema::parse(SCHEMA).expect("ERROR: Couldn't parse schema");
+        let page = metadata("---\ntitle: Hello\nrating: high\n---\n");
+
+        let violations = schema.validate("posts/hello.md", &page);
+
+        assert_eq!(violations, vec![
+            "missing required key \"date\"".to_string(),
+            "missing required key \"tags\"".to_string(),
+            "key \"rating\" should be float".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn validate_section_glob() {
+        let schema = Schema::parse("[posts/*]\ntitle = \"string\"\n").expect("ERROR: Couldn't parse schema");
+        let page = metadata("---\ndate: 2023-04-08\n---\n");
+
+        assert_eq!(schema.sections[0].section, "posts");
+        assert_eq!(schema.validate("posts/a.md", &page), vec!["missing required key \"title\"".to_string()]);
+    }
+
+    #[test]
+    fn validate_only_matching_sections() {
+        let schema = Schema::parse(SCHEMA).expect("ERROR: Couldn't parse schema");
+        let page = metadata("---\ntitle: About\n---\n");
+        let post = metadata("---\ntitle: Hello\ndate: 2023-04-08\ntags: [rust]\nrating: 4\n---\n");
+
+        assert!(schema.validate("about.md", &page).is_empty());
+        assert!(schema.validate("posts/hello.md", &post).is_empty());
+    }
+}
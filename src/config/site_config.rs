@@ -0,0 +1,521 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::parsers::markdown::Extensions;
+use crate::parsers::metadata::{AfDateTime, Value};
+
+pub static CONFIG_FILE_NAME: &str = "afskylia.toml";
+
+#[derive(Debug, PartialEq)]
+pub struct SiteConfig {
+    pub title: String,
+    /// Prefixed to absolute links such as the sitemap, e.g. `https://example.com`
+    pub base_url: String,
+    pub language: String,
+    pub dirs: Dirs,
+    pub server: ServerConfig,
+    pub lists: ListConfig,
+    pub markdown: Extensions,
+    /// Also write a `.gmi` Gemtext page next to every html page
+    pub gemtext: bool,
+    /// Minutes east of UTC for front matter dates without an offset
+    pub timezone: i32,
+    /// Take `lastmod` from the last git commit touching a file rather than
+    /// its mtime, which a fresh checkout resets
+    pub git_lastmod: bool,
+    /// Anything under `[params]`, for the layouts to use
+    pub params: BTreeMap<String, Value>,
+    /// Links that aren't pages, by menu, from `[menu.<name>]`
+    pub menus: BTreeMap<String, Vec<MenuLink>>,
+    /// File name patterns left out of the build, e.g. `*.swp`. `*` matches
+    /// anything and `?` any one character
+    pub ignore_files: Vec<String>,
+}
+
+/// The config sections besides `[menu.<name>]`.
+static KNOWN_SECTIONS: [&str; 6] = ["dirs", "server", "lists", "params", "markdown", "output"];
+
+/// Editor swap and backup files, always ignored.
+static DEFAULT_IGNORE_FILES: [&str; 8] = ["*.swp", "*.swo", "*.swx", "*~", ".#*", "#*#", ".DS_Store", "4913"];
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        return SiteConfig {
+            title: String::default(),
+            base_url: String::default(),
+            language: "en".to_string(),
+            dirs: Dirs::default(),
+            server: ServerConfig::default(),
+            lists: ListConfig::default(),
+            markdown: Extensions::default(),
+            gemtext: false,
+            timezone: 0,
+            git_lastmod: false,
+            params: BTreeMap::new(),
+            menus: BTreeMap::new(),
+            ignore_files: DEFAULT_IGNORE_FILES.iter().map(|x| x.to_string()).collect(),
+        };
+    }
+}
+
+/// Where the site keeps its files, relative to the site root.
+#[derive(Debug, PartialEq)]
+pub struct Dirs {
+    pub content: String,
+    pub layouts: String,
+    pub resources: String,
+    pub public: String,
+}
+
+impl Default for Dirs {
+    fn default() -> Self {
+        return Dirs {
+            content: "content".to_string(),
+            layouts: "layouts".to_string(),
+            resources: "resources".to_string(),
+            public: "public".to_string(),
+        };
+    }
+}
+
+/// The address `afskylia run` serves the site on.
+#[derive(Debug, PartialEq)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        return ServerConfig { host: "127.0.0.1".to_string(), port: 1313 };
+    }
+}
+
+/// How section list pages are sorted and paginated. A section's own page,
+/// e.g. `posts.md`, can override these in its front matter.
+#[derive(Debug, PartialEq)]
+pub struct ListConfig {
+    /// Pages per list page, 0 puts them all on one
+    pub page_size: usize,
+    /// `date`, `weight`, `title` or any front matter key
+    pub sort_by: String,
+    /// `Some(true)` for `sort_order = "desc"`, `None` for the key's default
+    pub descending: Option<bool>,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        return ListConfig { page_size: 10, sort_by: "date".to_string(), descending: None };
+    }
+}
+
+/// A menu entry from the config, e.g. an external link:
+/// `GitHub = { url = "https://github.com/me", weight = 10, parent = "About" }`
+#[derive(Debug, PartialEq)]
+pub struct MenuLink {
+    pub name: String,
+    pub url: String,
+    pub weight: i64,
+    /// The name of the entry this one is nested under
+    pub parent: Option<String>,
+}
+
+impl MenuLink {
+    fn parse(name: &str, value: Value, line_index: usize) -> Result<MenuLink, String> {
+        let invalid = |expected: &str| format!("ERROR: Expected {expected} for menu entry {name:?} on line {}", line_index+1);
+        let Value::Table(table) = value else {
+            return Err(invalid("a table like { url = \"/about.html\" }"));
+        };
+
+        let mut link = MenuLink { name: name.to_string(), url: String::default(), weight: 0, parent: None };
+        for (key, value) in table {
+            match key.as_str() {
+                "url" => link.url = value.as_str().ok_or_else(|| invalid("a string url"))?.to_string(),
+                "weight" => link.weight = value.as_integer().ok_or_else(|| invalid("an integer weight"))?,
+                "parent" => link.parent = Some(value.as_str().ok_or_else(|| invalid("a string parent"))?.to_string()),
+                _ => return Err(format!("ERROR: Unknown menu setting {key:?} on line {}", line_index+1)),
+            }
+        }
+        if link.url.is_empty() {
+            return Err(invalid("a url"));
+        }
+        return Ok(link);
+    }
+}
+
+impl SiteConfig {
+    /// Reads `afskylia.toml`, or the file given with `--config`, from the site
+    /// root. A site without a config file gets the default configuration, but
+    /// a config file that was asked for has to exist.
+    pub fn load(base_dir: &Path, config_file: Option<&Path>) -> Result<SiteConfig, String> {
+        let config_path = base_dir.join(config_file.unwrap_or(Path::new(CONFIG_FILE_NAME)));
+        if !config_path.is_file() {
+            return match config_file {
+                Some(_) => Err(format!("ERROR: Config file {} doesn't exist", config_path.display())),
+                None => Ok(SiteConfig::default()),
+            };
+        }
+
+        let input = fs::read_to_string(&config_path).map_err(|e| format!("ERROR: Couldn't read {}: {e}", config_path.display()))?;
+        return SiteConfig::parse(&input);
+    }
+
+    pub fn parse(input: &str) -> Result<SiteConfig, String> {
+        let mut config = SiteConfig::default();
+        let mut section = String::default();
+
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let header = match header.split_once(']') {
+                    Some((header, rest)) if rest.trim().is_empty() || rest.trim().starts_with('#') => header,
+                    _ => return Err(format!("ERROR: Expected [section] on line {}", i+1)),
+                };
+                section = header.trim().to_string();
+                if !KNOWN_SECTIONS.contains(&section.as_str()) && !section.starts_with("menu.") {
+                    return Err(format!("ERROR: Unknown section [{section}] on line {}", i+1));
+                }
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(format!("ERROR: Expected key = value on line {}", i+1)),
+            };
+            // Every value is read like front matter, so quoting and `# comments` work the same everywhere
+            let value = Value::parse(value).map_err(|e| format!("ERROR: {} for {key} on line {}", e.kind, i+1))?;
+            let expected = |what: &str| format!("ERROR: Expected {what} for {key} on line {}", i+1);
+            let string = |value: &Value| value.as_str().map(|x| x.to_string()).ok_or_else(|| expected("a string"));
+            let boolean = |value: &Value| value.as_bool().ok_or_else(|| expected("true or false"));
+
+            if section.is_empty() {
+                match key {
+                    "title" => config.title = string(&value)?,
+                    "base_url" => config.base_url = string(&value)?.trim_end_matches('/').to_string(),
+                    "language" => config.language = string(&value)?,
+                    "timezone" => {
+                        config.timezone = AfDateTime::parse_offset(&string(&value)?).map_err(|e| format!("ERROR: {e} on line {}", i+1))?;
+                    },
+                    "ignore_files" => {
+                        let patterns = value.as_array().ok_or_else(|| expected("a list of patterns"))?;
+                        for pattern in patterns {
+                            config.ignore_files.push(pattern.as_str().ok_or_else(|| expected("a list of patterns"))?.to_string());
+                        }
+                    },
+                    "git_lastmod" => config.git_lastmod = boolean(&value)?,
+                    _ => return Err(format!("ERROR: Unknown key {key:?} on line {}", i+1)),
+                }
+            }
+
+            if section == "dirs" {
+                let dir = string(&value)?;
+                match key {
+                    "content" => config.dirs.content = dir,
+                    "layouts" => config.dirs.layouts = dir,
+                    "resources" => config.dirs.resources = dir,
+                    "public" => config.dirs.public = dir,
+                    _ => return Err(format!("ERROR: Unknown dir {key:?} on line {}", i+1)),
+                }
+            }
+
+            if section == "server" {
+                match key {
+                    "host" => config.server.host = string(&value)?,
+                    "port" => config.server.port = value.as_integer().and_then(|x| u16::try_from(x).ok()).ok_or_else(|| expected("a port number"))?,
+                    _ => return Err(format!("ERROR: Unknown server setting {key:?} on line {}", i+1)),
+                }
+            }
+
+            if section == "lists" {
+                match key {
+                    "page_size" => config.lists.page_size = value.as_integer().and_then(|x| usize::try_from(x).ok()).ok_or_else(|| expected("a number"))?,
+                    "sort_by" => config.lists.sort_by = string(&value)?,
+                    "sort_order" => config.lists.descending = Some(parse_sort_order(&string(&value)?).ok_or_else(|| expected("\"asc\" or \"desc\""))?),
+                    _ => return Err(format!("ERROR: Unknown list setting {key:?} on line {}", i+1)),
+                }
+            }
+
+            if section == "params" {
+                config.params.insert(key.trim_matches('"').to_string(), value);
+                continue;
+            }
+
+            if let Some(menu) = section.strip_prefix("menu.") {
+                let link = MenuLink::parse(key.trim_matches('"'), value, i)?;
+                config.menus.entry(menu.trim_matches('"').to_string()).or_default().push(link);
+                continue;
+            }
+
+            if section == "markdown" {
+                let enabled = boolean(&value)?;
+                match key {
+                    "definition_lists" => config.markdown.definition_lists = enabled,
+                    "abbreviations" => config.markdown.abbreviations = enabled,
+                    "figures" => config.markdown.figures = enabled,
+                    "emoji" => config.markdown.emoji = enabled,
+                    _ => return Err(format!("ERROR: Unknown markdown extension {key:?} on line {}", i+1)),
+                }
+            }
+
+            if section == "output" {
+                let enabled = boolean(&value)?;
+                match key {
+                    "gemtext" => config.gemtext = enabled,
+                    _ => return Err(format!("ERROR: Unknown output format {key:?} on line {}", i+1)),
+                }
+            }
+        }
+
+        return Ok(config);
+    }
+
+    /// Whether a file or dir is left out of the build by its name.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name().and_then(|x| x.to_str()) else {
+            return false;
+        };
+        return self.ignore_files.iter().any(|x| matches_pattern(x, file_name));
+    }
+}
+
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+    // Where the last `*` was and how much of `name` it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    return pattern[p..].iter().all(|x| *x == '*');
+}
+
+/// `asc` or `desc`, as whether the order is descending.
+pub fn parse_sort_order(value: &str) -> Option<bool> {
+    return match value {
+        "asc" => Some(false),
+        "desc" => Some(true),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs;
+    use std::path::Path;
+
+    use claim::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use crate::parsers::metadata::Value;
+
+    use super::{MenuLink, SiteConfig};
+
+    #[test]
+    fn parse_empty_is_default() {
+        let config = SiteConfig::parse("").expect("ERROR: Couldn't parse config");
+
+        assert_eq!(config, SiteConfig::default());
+    }
+
+    #[test]
+    fn parse_markdown_extensions() {
+        let input = "# Site config\n\
+                     [markdown]\n\
+                     definition_lists = false\n\
+                     abbreviations = true\n\
+                     figures = true\n\
+                     emoji = false\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert!(!config.markdown.definition_lists);
+        assert!(config.markdown.abbreviations);
+        assert!(config.markdown.figures);
+        assert!(!config.markdown.emoji);
+    }
+
+    #[test]
+    fn parse_timezone() {
+        let input = "timezone = \"+02:00\"\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert_eq!(config.timezone, 120);
+        assert!(!config.git_lastmod);
+        assert_err!(SiteConfig::parse("timezone = \"Europe/Oslo\"\n"));
+    }
+
+    #[test]
+    fn parse_output_formats() {
+        let input = "[output]\n\
+                     gemtext = true\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert!(config.gemtext);
+    }
+
+    #[test]
+    fn parse_invalid_bool_is_rejected() {
+        let input = "[markdown]\n\
+                     abbreviations = yes\n";
+
+        assert_err!(SiteConfig::parse(input));
+    }
+
+    #[test]
+    fn parse_unknown_extension_is_rejected() {
+        let input = "[markdown]\n\
+                     tables = true\n";
+
+        assert_err!(SiteConfig::parse(input));
+    }
+
+    #[test]
+    fn parse_values_with_comments() {
+        let input = "git_lastmod = true # from git\n\
+                     [server] # dev server\n\
+                     port = 8080 # dev\n\
+                     [lists]\n\
+                     page_size = 5\t# per page\n\
+                     [output]\n\
+                     gemtext = true # gemini\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert!(config.git_lastmod);
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.lists.page_size, 5);
+        assert!(config.gemtext);
+        assert_err!(SiteConfig::parse("[server]\nport = 70000\n"));
+    }
+
+    #[test]
+    fn parse_unknown_section_is_rejected() {
+        assert_err!(SiteConfig::parse("[Markdown]\nemoji = false\n"));
+        assert_err!(SiteConfig::parse("[sever]\nport = 8080\n"));
+        assert_err!(SiteConfig::parse("[server\nport = 8080\n"));
+    }
+
+    #[test]
+    fn parse_site_settings() {
+        let input = "title = \"My Site\"\n\
+                     base_url = \"https://example.com/\"\n\
+                     language = \"nb\"\n\
+                     \n\
+                     [dirs]\n\
+                     content = \"pages\"\n\
+                     public = \"dist\"\n\
+                     \n\
+                     [server]\n\
+                     host = \"0.0.0.0\"\n\
+                     port = 8080\n\
+                     \n\
+                     [params]\n\
+                     author = \"Jane\"\n\
+                     social = [\"mastodon\", \"github\"]\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert_eq!(config.title, "My Site");
+        assert_eq!(config.base_url, "https://example.com");
+        assert_eq!(config.language, "nb");
+        assert_eq!(config.dirs.content, "pages");
+        assert_eq!(config.dirs.layouts, "layouts");
+        assert_eq!(config.dirs.public, "dist");
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.params["author"], Value::String("Jane".to_string()));
+        assert_eq!(config.params["social"].as_array().map(|x| x.len()), Some(2));
+    }
+
+    #[test]
+    fn parse_list_settings() {
+        let input = "[lists]\n\
+                     page_size = 5\n\
+                     sort_by = \"title\"\n\
+                     sort_order = \"desc\"\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        assert_eq!(config.lists.page_size, 5);
+        assert_eq!(config.lists.sort_by, "title");
+        assert_eq!(config.lists.descending, Some(true));
+        assert_err!(SiteConfig::parse("[lists]\nsort_order = \"up\"\n"));
+    }
+
+    #[test]
+    fn parse_menu_links() {
+        let input = "[menu.main]\n\
+                     GitHub = { url = \"https://github.com/me\", weight = 10 }\n\
+                     \"Old blog\" = { url = \"https://old.example.com\", parent = \"About\" }\n";
+
+        let config = SiteConfig::parse(input).expect("ERROR: Couldn't parse config");
+
+        let links = &config.menus["main"];
+        assert_eq!(links[0], MenuLink { name: "GitHub".to_string(), url: "https://github.com/me".to_string(), weight: 10, parent: None });
+        assert_eq!(links[1].name, "Old blog");
+        assert_eq!(links[1].parent.as_deref(), Some("About"));
+        assert_err!(SiteConfig::parse("[menu.main]\nGitHub = \"https://github.com/me\"\n"));
+        assert_err!(SiteConfig::parse("[menu.main]\nGitHub = { weight = 1 }\n"));
+        assert_err!(SiteConfig::parse("[menu.main]\nGitHub = { url = \"/\", icon = \"gh\" }\n"));
+    }
+
+    #[test]
+    fn is_ignored_matches_swap_files_and_patterns() {
+        let config = SiteConfig::parse("ignore_files = [\"*.bak\", \"draft-?.md\"]\n").expect("ERROR: Couldn't parse config");
+
+        assert!(config.is_ignored(Path::new("content/.hello.md.swp")));
+        assert!(config.is_ignored(Path::new("resources/style.css~")));
+        assert!(config.is_ignored(Path::new("content/#hello.md#")));
+        assert!(config.is_ignored(Path::new("resources/logo.png.bak")));
+        assert!(config.is_ignored(Path::new("content/draft-1.md")));
+        assert!(!config.is_ignored(Path::new("content/draft-10.md")));
+        assert!(!config.is_ignored(Path::new("content/hello.md")));
+        assert_err!(SiteConfig::parse("ignore_files = \"*.bak\"\n"));
+    }
+
+    #[test]
+    fn parse_invalid_site_settings_are_rejected() {
+        assert_err!(SiteConfig::parse("title = 42\n"));
+        assert_err!(SiteConfig::parse("theme = \"dark\"\n"));
+        assert_err!(SiteConfig::parse("[server]\nport = 70000\n"));
+        assert_err!(SiteConfig::parse("[dirs]\nstatic = \"static\"\n"));
+    }
+
+    #[test]
+    fn load_alternate_config_file() {
+        let base_dir = temp_dir().join("rust").join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&base_dir).expect("ERROR: couldn't create base_dir");
+        fs::write(base_dir.join("staging.toml"), "title = \"Staging\"\n").expect("ERROR: couldn't write config file");
+
+        let config = SiteConfig::load(&base_dir, Some(Path::new("staging.toml")));
+        let missing = SiteConfig::load(&base_dir, Some(Path::new("missing.toml")));
+        let default = SiteConfig::load(&base_dir, None);
+
+        assert_ok!(fs::remove_dir_all(&base_dir));
+        assert_eq!(config.expect("ERROR: Couldn't load config").title, "Staging");
+        assert_err!(missing);
+        assert_eq!(default.expect("ERROR: Couldn't load config"), SiteConfig::default());
+    }
+}
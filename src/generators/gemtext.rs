@@ -0,0 +1,144 @@
+use crate::generators::renderer::{Renderer, Image};
+
+/// Renders markdown to Gemtext for Gemini capsules. Gemtext has no inline
+/// links, so links and images found in a block are collected and written as
+/// `=>` lines after it.
+#[derive(Default)]
+pub struct GemtextRenderer {
+    links: Vec<(String, String)>,
+}
+
+impl GemtextRenderer {
+    pub fn new() -> Self {
+        return GemtextRenderer::default();
+    }
+
+    fn take_link_lines(&mut self) -> String {
+        let lines = self.links.iter()
+            .map(|(href, content)| format!("=> {href} {content}\n"))
+            .collect::<String>();
+        self.links.clear();
+        return lines;
+    }
+}
+
+impl Renderer for GemtextRenderer {
+    fn heading(&mut self, level: usize, content: &str) -> String {
+        // Gemtext only has three heading levels
+        let marker = "#".repeat(level.min(3));
+        return format!("{marker} {content}\n{}\n", self.take_link_lines());
+    }
+
+    fn paragraph(&mut self, content: &str) -> String {
+        return format!("{content}\n{}\n", self.take_link_lines());
+    }
+
+    fn ordered_list(&mut self, items: &[String]) -> String {
+        let list = items.iter().map(|item| format!("* {item}\n")).collect::<String>();
+        return format!("{list}{}\n", self.take_link_lines());
+    }
+
+    fn definition_list(&mut self, entries: &[(String, Vec<String>)]) -> String {
+        let mut str_vec: Vec<String> = vec![];
+        for (term, definitions) in entries {
+            str_vec.push(format!("{term}\n"));
+            for definition in definitions {
+                str_vec.push(format!("* {definition}\n"));
+            }
+        }
+        return format!("{}{}\n", str_vec.join(""), self.take_link_lines());
+    }
+
+    fn code_block(&mut self, language: &str, code: &str) -> String {
+        return format!("```{language}\n{code}\n```\n\n");
+    }
+
+    fn figure(&mut self, image: &Image, caption: Option<&str>) -> String {
+        return format!("=> {} {}\n\n", image.src, caption.unwrap_or(&image.alt));
+    }
+
+    fn inline_block(&mut self, content: &str) -> String {
+        // A lone link or image is just its link line
+        if !self.links.is_empty() {
+            return format!("{}\n", self.take_link_lines());
+        }
+        return format!("{content}\n\n");
+    }
+
+    fn text(&mut self, text: &str) -> String {
+        return text.to_string();
+    }
+
+    fn abbreviation(&mut self, abbr: &str, _title: &str) -> String {
+        return abbr.to_string();
+    }
+
+    fn link(&mut self, href: &str, content: &str) -> String {
+        self.links.push((href.to_string(), content.to_string()));
+        return content.to_string();
+    }
+
+    fn image(&mut self, image: &Image) -> String {
+        let description = image.title.clone().unwrap_or(image.alt.to_string());
+        self.links.push((image.src.to_string(), description));
+        return image.alt.to_string();
+    }
+
+    fn code_span(&mut self, code: &str) -> String {
+        return code.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::Generator;
+    use crate::parsers::markdown::{Lexer, Extensions};
+
+    use super::GemtextRenderer;
+
+    fn get_gemtext(input: &str) -> String {
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+        let mut generator = Generator::with_renderer(lexer, Extensions::default(), GemtextRenderer::new());
+        return generator.generate().expect("ERROR: Couldn't get gemtext");
+    }
+
+    #[test]
+    fn get_gemtext_heading_levels() {
+        let input = "# A\n\
+                     #### B";
+        let expected = "# A\n\n### B\n\n";
+
+        assert_eq!(get_gemtext(input), expected);
+    }
+
+    #[test]
+    fn get_gemtext_moves_links_after_paragraph() {
+        let input = "Read [the docs](https://example.com) and [more](/more)\n\
+                     \n\
+                     Next";
+        let expected = "Read the docs and more\n=> https://example.com the docs\n=> /more more\n\nNext\n\n";
+
+        assert_eq!(get_gemtext(input), expected);
+    }
+
+    #[test]
+    fn get_gemtext_standalone_link() {
+        let input = "[Home](/)";
+        let expected = "=> / Home\n\n";
+
+        assert_eq!(get_gemtext(input), expected);
+    }
+
+    #[test]
+    fn get_gemtext_list_and_code() {
+        let input = "1. A\n\
+                     2. B\n\
+                     \n\
+                     ```sh\n\
+                     ls\n\
+                     ```";
+        let expected = "* A\n* B\n\n```sh\nls\n```\n\n";
+
+        assert_eq!(get_gemtext(input), expected);
+    }
+}
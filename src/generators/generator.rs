@@ -0,0 +1,465 @@
+use crate::generators::renderer::{Renderer, Image};
+use crate::parsers::markdown::{Lexer, Token, TokenType, Extensions, Abbreviation, Attributes, Diagnostic, DiagnosticKind, find_abbreviations, replace_shortcodes};
+
+/// Walks the markdown tokens and drives a `Renderer` for every block and
+/// inline element it finds.
+pub struct Generator<R: Renderer> {
+    lexer: Lexer,
+    renderer: R,
+    extensions: Extensions,
+    abbreviations: Vec<Abbreviation>,
+    warnings: Vec<Diagnostic>,
+}
+
+impl<R: Renderer> Generator<R> {
+    pub fn with_renderer(lexer: Lexer, extensions: Extensions, renderer: R) -> Generator<R> {
+        let mut abbreviations = vec![];
+        if extensions.abbreviations {
+            abbreviations = find_abbreviations(lexer.get_input());
+        }
+
+        return Generator { lexer, renderer, extensions, abbreviations, warnings: vec![] };
+    }
+
+    pub fn generate(&mut self) -> Result<String, String> {
+        let mut str_vec: Vec<String> = vec![];
+
+        let mut i = self.lexer.next_token();
+        while i.token_type != TokenType::EOF {
+            if i.token_type == TokenType::EOF {
+                break;
+            }
+
+            let token_output = self.get_output_for_token(i)?;
+            str_vec.push(token_output);
+
+            i = self.lexer.next_token();
+        }
+
+        return Ok(str_vec.join(""));
+    }
+
+    pub fn get_warnings(&self) -> &[Diagnostic] {
+        return &self.warnings;
+    }
+
+    fn get_output_for_token(&mut self, token: Token) -> Result<String, String> {
+        let str = match token.token_type {
+            TokenType::Heading => {
+
+                let mut heading_level = 1_usize;
+
+                let mut i = self.lexer.next_token();
+                while i.token_type == TokenType::Heading {
+                    heading_level += 1;
+                    i = self.lexer.next_token();
+                };
+
+                if heading_level > 6 {
+                    self.warn(DiagnosticKind::UnknownConstruct(format!("heading level {heading_level}")), &token);
+                    heading_level = 6;
+                }
+
+                // Inline markup such as code spans is parsed, so `:rocket:` in backticks stays as is
+                let content = self.get_line_output(i);
+                self.renderer.heading(heading_level, &content)
+            },
+            TokenType::Letter if self.is_abbreviation_definition(&token) => {
+                self.skip_line();
+                String::default()
+            },
+            TokenType::Letter if self.is_definition_term() => {
+                self.generate_definition_list(token)
+            },
+            TokenType::Letter | TokenType::LBracket | TokenType::RParen | TokenType::LParen => {
+                self.check_unsupported_block(&token);
+                self.generate_paragraph(token)
+            },
+            TokenType::EOF => String::from(""),
+            TokenType::OrderedItem => {
+                let mut items: Vec<String> = vec![];
+                let mut text = String::default();
+                let mut i = self.lexer.next_token();
+                while i.token_type != TokenType::EOF
+                    &&
+                    !(i.token_type == TokenType::LineBreak
+                      && (self.lexer.peek_next_token().token_type == TokenType::LineBreak ||
+                          self.lexer.peek_next_token().token_type == TokenType::EOF)) {
+
+                    if i.token_type == TokenType::LineBreak {
+                        items.push(self.render_text(&text));
+                        text.clear();
+                        self.lexer.next_token();
+                        i = self.lexer.next_token();
+                        continue;
+                    }
+                    text.push_str(&i.literal);
+                    i = self.lexer.next_token();
+                };
+                items.push(self.render_text(&text));
+                self.renderer.ordered_list(&items)
+            },
+            TokenType::RBracket if self.check_link(&token) => {
+                let link = self.generate_link();
+                self.renderer.inline_block(&link)
+            },
+            TokenType::Bang if self.check_image() => {
+                self.generate_block_image()
+            },
+            TokenType::Backtick if self.is_fence() => {
+                self.generate_code_block(&token)
+            },
+            TokenType::RBracket | TokenType::Bang | TokenType::Backtick => {
+                self.generate_paragraph(token)
+            },
+            TokenType::LineBreak => String::from(""),
+            _ => {
+                self.warn(DiagnosticKind::UnknownConstruct(token.token_type.to_string()), &token);
+                self.generate_paragraph(token)
+            },
+        };
+        return Ok(str);
+    }
+
+    fn warn(&mut self, kind: DiagnosticKind, token: &Token) {
+        // A block that falls back to a paragraph checks its first token again
+        let diagnostic = Diagnostic::new(kind, token.line, token.column);
+        if !self.warnings.contains(&diagnostic) {
+            self.warnings.push(diagnostic);
+        }
+    }
+
+    fn generate_paragraph(&mut self, token: Token) -> String {
+        let mut str_vec: Vec<String> = vec![];
+        let mut text = String::default();
+        self.push_inline(token, &mut text, &mut str_vec);
+        let mut next_token = self.lexer.next_token();
+        while next_token.token_type != TokenType::EOF {
+            let peek_token = self.lexer.peek_next_token();
+
+            if next_token.token_type == TokenType::LineBreak
+                &&
+                (peek_token.token_type == TokenType::LineBreak
+                 ||
+                 peek_token.token_type == TokenType::EOF
+                 ||
+                 peek_token.token_type == TokenType::OrderedItem
+                 ||
+                 self.lexer.peek_line(0).is_some_and(|line| line.starts_with("```"))
+                ) {
+                break;
+            }
+
+            self.push_inline(next_token, &mut text, &mut str_vec);
+            next_token = self.lexer.next_token();
+        };
+        str_vec.push(self.render_text(&text));
+        return self.renderer.paragraph(&str_vec.join(""));
+    }
+
+    /// Blocks we don't render yet are kept as paragraphs, but flagged so the
+    /// author knows the markup had no effect.
+    fn check_unsupported_block(&mut self, token: &Token) {
+        let line = format!("{}{}", token.literal, self.lexer.peek_line(0).unwrap_or_default());
+        let construct = match line {
+            l if l.starts_with("- ") || l.starts_with("* ") || l.starts_with("+ ") => "unordered list",
+            l if l.starts_with("> ") => "blockquote",
+            l if l.starts_with('|') => "table",
+            _ => return,
+        };
+        self.warn(DiagnosticKind::UnknownConstruct(construct.to_string()), token);
+    }
+
+    fn push_inline(&mut self, token: Token, text: &mut String, str_vec: &mut Vec<String>) {
+        let output = match token.token_type {
+            TokenType::RBracket if self.check_link(&token) => self.generate_link(),
+            TokenType::Bang if self.check_image() => {
+                let image = self.parse_image();
+                self.renderer.image(&image)
+            },
+            TokenType::Backtick if self.is_code_span() => self.generate_code_span(),
+            _ => {
+                text.push_str(&token.literal);
+                return;
+            }
+        };
+
+        str_vec.push(self.render_text(text));
+        text.clear();
+        str_vec.push(output);
+    }
+
+    fn is_abbreviation_definition(&self, token: &Token) -> bool {
+        if !self.extensions.abbreviations || token.literal != "*" {
+            return false;
+        }
+
+        let line = format!("*{}", self.lexer.peek_line(0).unwrap_or_default());
+        return Abbreviation::parse_line(&line).is_some();
+    }
+
+    fn is_definition_term(&self) -> bool {
+        if !self.extensions.definition_lists {
+            return false;
+        }
+
+        return match self.lexer.peek_line(1) {
+            Some(line) => line.starts_with(": "),
+            None => false,
+        };
+    }
+
+    fn skip_line(&mut self) {
+        let mut next_token = self.lexer.next_token();
+        while next_token.token_type != TokenType::LineBreak && next_token.token_type != TokenType::EOF {
+            next_token = self.lexer.next_token();
+        }
+    }
+
+    fn generate_definition_list(&mut self, token: Token) -> String {
+        let mut entries: Vec<(String, Vec<String>)> = vec![];
+        let mut term_token = token;
+        loop {
+            let term = self.get_line_output(term_token);
+            let mut definitions = vec![];
+
+            while self.lexer.peek_line(0).is_some_and(|line| line.starts_with(": ")) {
+                self.lexer.next_token(); // :
+                self.lexer.next_token(); // space
+                let definition_token = self.lexer.next_token();
+                definitions.push(self.get_line_output(definition_token));
+            }
+            entries.push((term, definitions));
+
+            // A following term may be separated from the previous definition by one blank line
+            let mut offset = 0;
+            if self.lexer.peek_line(0).is_some_and(|line| line.is_empty()) {
+                offset = 1;
+            }
+            let next_is_term = self.lexer.peek_line(offset).is_some_and(|line| !line.is_empty())
+                && self.lexer.peek_line(offset + 1).is_some_and(|line| line.starts_with(": "));
+            if !next_is_term {
+                break;
+            }
+
+            if offset == 1 {
+                self.lexer.next_token();
+            }
+            term_token = self.lexer.next_token();
+        }
+        return self.renderer.definition_list(&entries);
+    }
+
+    fn get_line_output(&mut self, token: Token) -> String {
+        let mut str_vec: Vec<String> = vec![];
+        let mut text = String::default();
+        let mut next_token = token;
+        while next_token.token_type != TokenType::LineBreak && next_token.token_type != TokenType::EOF {
+            self.push_inline(next_token, &mut text, &mut str_vec);
+            next_token = self.lexer.next_token();
+        }
+        str_vec.push(self.render_text(&text));
+        return str_vec.join("");
+    }
+
+    fn render_text(&mut self, text: &str) -> String {
+        let mut text = text.to_string();
+        if self.extensions.emoji {
+            text = replace_shortcodes(&text);
+        }
+
+        let chars = text.chars().collect::<Vec<char>>();
+        let mut res = String::default();
+        let mut run = String::default();
+        let mut i = 0;
+        'outer: while i < chars.len() {
+            let at_word_start = i == 0 || !chars[i-1].is_alphanumeric();
+            if at_word_start {
+                for abbreviation in &self.abbreviations {
+                    let abbr = abbreviation.abbr.chars().collect::<Vec<char>>();
+                    let end = i + abbr.len();
+                    let at_word_end = end == chars.len() || (end < chars.len() && !chars[end].is_alphanumeric());
+                    if end <= chars.len() && chars[i..end] == abbr[..] && at_word_end {
+                        res.push_str(&self.renderer.text(&run));
+                        run.clear();
+                        res.push_str(&self.renderer.abbreviation(&abbreviation.abbr, &abbreviation.title));
+                        i = end;
+                        continue 'outer;
+                    }
+                }
+            }
+            run.push(chars[i]);
+            i += 1;
+        }
+        res.push_str(&self.renderer.text(&run));
+        return res;
+    }
+
+    fn is_fence(&self) -> bool {
+        return self.lexer.peek_line(0).is_some_and(|line| line.starts_with("``"));
+    }
+
+    fn is_code_span(&self) -> bool {
+        return self.lexer.peek_line(0).is_some_and(|line| line.contains('`'));
+    }
+
+    fn generate_code_block(&mut self, token: &Token) -> String {
+        let info = self.lexer.read_line().unwrap_or_default();
+        let language = info.trim_start_matches('`').trim().to_string();
+
+        let mut lines: Vec<String> = vec![];
+        let mut is_closed = false;
+        while let Some(line) = self.lexer.read_line() {
+            if line.trim_end() == "```" {
+                is_closed = true;
+                break;
+            }
+            lines.push(line);
+        }
+
+        if !is_closed {
+            self.warn(DiagnosticKind::UnclosedFence, token);
+        }
+
+        return self.renderer.code_block(&language, &lines.join("\n"));
+    }
+
+    fn generate_code_span(&mut self) -> String {
+        let rest_of_line = self.lexer.peek_line(0).unwrap_or_default();
+        let code = rest_of_line.split('`').next().unwrap_or_default().to_string();
+        self.lexer.skip_chars(code.chars().count() + 1);
+
+        return self.renderer.code_span(&code);
+    }
+
+    /// Checks that the `[` just read starts a complete `[text](href)` on the
+    /// same line. Brackets that never close, or a `](` without a closing
+    /// paren, are reported as unclosed links.
+    fn check_link(&mut self, token: &Token) -> bool {
+        return match self.find_link(0) {
+            Some(is_link) => is_link,
+            None => {
+                self.warn(DiagnosticKind::UnclosedLink, token);
+                false
+            }
+        };
+    }
+
+    /// An unclosed image isn't reported here, as its `[` is then read as a
+    /// link, which reports it once.
+    fn check_image(&mut self) -> bool {
+        if self.lexer.peek_next_token().token_type != TokenType::RBracket {
+            return false;
+        }
+
+        return self.find_link(1) == Some(true);
+    }
+
+    /// Whether the `[` at `start` starts a link, is plain text in brackets, or
+    /// `None` when it is never closed.
+    fn find_link(&self, start: usize) -> Option<bool> {
+        let mut i = start;
+        let mut peek_token = self.lexer.peek_nth_token(i);
+        loop {
+            if peek_token.token_type == TokenType::EOF || peek_token.token_type == TokenType::LineBreak {
+                break;
+            }
+
+            if peek_token.token_type == TokenType::LBracket {
+                if self.lexer.peek_nth_token(i+1).token_type != TokenType::RParen {
+                    // plain text in brackets, like [note]
+                    return Some(false);
+                }
+
+                let mut j = i + 2;
+                loop {
+                    match self.lexer.peek_nth_token(j).token_type {
+                        TokenType::LParen => return Some(true),
+                        TokenType::EOF | TokenType::LineBreak => break,
+                        _ => j += 1,
+                    }
+                }
+                break;
+            }
+
+            i += 1;
+            peek_token = self.lexer.peek_nth_token(i);
+        }
+
+        return None;
+    }
+
+    fn generate_link(&mut self) -> String {
+        let mut next_token = self.lexer.next_token();
+        let mut title = String::default();
+        while next_token.token_type != TokenType::LBracket {
+            title.push_str(&next_token.literal);
+            next_token = self.lexer.next_token();
+        }
+
+        let mut href = String::default();
+        self.lexer.next_token(); // (
+        next_token = self.lexer.next_token();
+        while next_token.token_type != TokenType::LParen {
+            href.push_str(&next_token.literal);
+            next_token = self.lexer.next_token();
+        }
+
+        return self.renderer.link(&href, &title);
+    }
+
+    fn generate_block_image(&mut self) -> String {
+        let image = self.parse_image();
+
+        let is_standalone = self.lexer.peek_line(0).is_none_or(|line| line.trim().is_empty());
+        if !self.extensions.figures || !is_standalone {
+            let output = self.renderer.image(&image);
+            return self.renderer.inline_block(&output);
+        }
+
+        let caption = image.title.as_ref().map(|title| self.render_text(title));
+        return self.renderer.figure(&image, caption.as_deref());
+    }
+
+    fn parse_image(&mut self) -> Image {
+        self.lexer.next_token(); // [
+        let mut next_token = self.lexer.next_token();
+        let mut alt = String::default();
+        while next_token.token_type != TokenType::LBracket {
+            alt.push_str(&next_token.literal);
+            next_token = self.lexer.next_token();
+        }
+
+        let mut src = String::default();
+        self.lexer.next_token(); // (
+        next_token = self.lexer.next_token();
+        while next_token.token_type == TokenType::Letter && next_token.literal != " " {
+            src.push_str(&next_token.literal);
+            next_token = self.lexer.next_token();
+        }
+        if next_token.literal == " " {
+            next_token = self.lexer.next_token(); // space
+        }
+
+        let mut title = String::default();
+        while next_token.token_type != TokenType::LParen && next_token.token_type != TokenType::EOF {
+            title.push_str(&next_token.literal);
+            next_token = self.lexer.next_token();
+        }
+        // Titles may be quoted, ![alt](src "title"), or bare for backwards compatibility
+        let title = title.trim();
+        let title = title.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(title);
+
+        let mut attributes = Attributes::default();
+        let rest_of_line = self.lexer.peek_line(0).unwrap_or_default();
+        if let Some(end) = rest_of_line.find('}').filter(|_| rest_of_line.starts_with('{')) {
+            if let Some(parsed) = Attributes::parse(&rest_of_line[1..end]) {
+                attributes = parsed;
+                self.lexer.skip_chars(rest_of_line[..=end].chars().count());
+            }
+        }
+
+        let title = if title.is_empty() { None } else { Some(title.to_string()) };
+        return Image { src, alt, title, attributes };
+    }
+}
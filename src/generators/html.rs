@@ -1,233 +1,488 @@
-use crate::parsers::markdown::{Lexer, Token, TokenType};
+use crate::generators::generator::Generator;
+use crate::generators::renderer::{Renderer, Image};
+use crate::parsers::markdown::{Lexer, Extensions};
 
-pub struct HtmlGenerator {
-    lexer: Lexer,
-}
+pub type HtmlGenerator = Generator<HtmlRenderer>;
 
 impl HtmlGenerator {
     pub fn new(lexer: Lexer) -> HtmlGenerator {
-        return HtmlGenerator { lexer };
+        return Self::with_extensions(lexer, Extensions::default());
+    }
+
+    pub fn with_extensions(lexer: Lexer, extensions: Extensions) -> HtmlGenerator {
+        return Generator::with_renderer(lexer, extensions, HtmlRenderer);
     }
 
     pub fn get_html(&mut self) -> Result<String, String> {
-        let mut str_vec: Vec<String> = vec![];
+        return self.generate();
+    }
+}
 
-        let mut i = self.lexer.next_token();
-        while i.token_type != TokenType::EOF {
-            if i.token_type == TokenType::EOF {
-                break;
-            }
+pub struct HtmlRenderer;
 
-            let token_html = self.get_html_for_token(i)?;
-            str_vec.push(token_html);
+impl Renderer for HtmlRenderer {
+    fn heading(&mut self, level: usize, content: &str) -> String {
+        return format!("<h{level}>{content}</h{level}>");
+    }
 
-            i = self.lexer.next_token();
-        }
+    fn paragraph(&mut self, content: &str) -> String {
+        return format!("<p>{content}</p>");
+    }
 
-        return Ok(str_vec.join(""));
-    }
-
-    fn get_html_for_token(&mut self, token: Token) -> Result<String, String> {
-        let str = match token.token_type {
-            TokenType::Heading => {
-
-                let mut heading_level = 1 as usize;
-
-                let mut i = self.lexer.next_token();
-                while i.token_type == TokenType::Heading {
-                    heading_level += 1;
-                    i = self.lexer.next_token();
-                };
-                
-                let mut str_vec: Vec<String> = vec![format!("<h{}>", heading_level)];
-
-                while i.token_type != TokenType::EOF {
-                    if i.token_type == TokenType::EOF || i.token_type == TokenType::LineBreak {
-                        break;
-                    }
-                    str_vec.push(i.literal);
-                    i = self.lexer.next_token();
-                };
-                str_vec.push(format!("</h{}>", heading_level));
-                str_vec.join("")
-            },
-            TokenType::Letter => {
-                let mut str_vec: Vec<String> = vec![format!("<p>{}", token.literal)];
-                let mut next_token = self.lexer.next_token();
-                while next_token.token_type != TokenType::EOF {
-                    let peek_token = self.lexer.peek_next_token();
-
-                    if next_token.token_type == TokenType::LineBreak 
-                        && 
-                        (peek_token.token_type == TokenType::LineBreak 
-                         || 
-                         peek_token.token_type == TokenType::EOF
-                         || 
-                         peek_token.token_type == TokenType::OrderedItem
-                        ) {
-                        break;
-                    }
-                    
-
-                    if next_token.token_type == TokenType::RBracket {
-                        str_vec.push(self.generate_link_html());
-                    } else if next_token.token_type == TokenType::Bang {
-                        str_vec.push(self.generate_image_html());
-                    }
-                    else {
-                        str_vec.push(next_token.literal);
-                    }
-                    next_token = self.lexer.next_token();
-                };
-                str_vec.push(format!("</p>"));
-                str_vec.join("")
-            },
-            TokenType::EOF => String::from(""),
-            TokenType::OrderedItem => {
-                let mut str_vec: Vec<String> = vec![format!("<ol><li>")];
-                let mut i = self.lexer.next_token();
-                while i.token_type != TokenType::EOF 
-                    && 
-                    !(i.token_type == TokenType::LineBreak 
-                      && (self.lexer.peek_next_token().token_type == TokenType::LineBreak || 
-                          self.lexer.peek_next_token().token_type == TokenType::EOF)) {
-
-                    if i.token_type == TokenType::LineBreak {
-                        str_vec.push(String::from("</li><li>"));
-                        self.lexer.next_token();
-                        i = self.lexer.next_token();
-                        continue;
-                    }
-                    str_vec.push(i.literal);
-                    i = self.lexer.next_token();
-                };
-                str_vec.push(format!("</li></ol>"));
-                str_vec.join("")
-            },
-            TokenType::RBracket => {
-                self.generate_link_html()
-            },
-            TokenType::Bang => {
-                self.generate_image_html()
+    fn ordered_list(&mut self, items: &[String]) -> String {
+        return format!("<ol><li>{}</li></ol>", items.join("</li><li>"));
+    }
+
+    fn definition_list(&mut self, entries: &[(String, Vec<String>)]) -> String {
+        let mut str_vec: Vec<String> = vec![String::from("<dl>")];
+        for (term, definitions) in entries {
+            str_vec.push(format!("<dt>{term}</dt>"));
+            for definition in definitions {
+                str_vec.push(format!("<dd>{definition}</dd>"));
             }
-            TokenType::LineBreak => String::from(""),
-            _ => {
-                unreachable!("Hit _ in html.rs, shouldn't happen");
-            },
+        }
+        str_vec.push(String::from("</dl>"));
+        return str_vec.join("");
+    }
+
+    fn code_block(&mut self, language: &str, code: &str) -> String {
+        let class = match language.is_empty() {
+            true => String::default(),
+            false => format!(" class=\"language-{}\"", escape_attribute(language)),
         };
-        return Ok(str);
+        return format!("<pre><code{class}>{}</code></pre>", escape_html(code));
     }
 
-    fn generate_link_html(&mut self) -> String {
-        let mut i = 1;
-        let mut peek_token = self.lexer.peek_nth_token(i);
-        let mut is_link = false;
-        loop {
-            if peek_token.token_type == TokenType::EOF {
-                break;
-            }
+    fn figure(&mut self, image: &Image, caption: Option<&str>) -> String {
+        let mut str_vec = vec![String::from("<figure>"), self.image(image)];
+        if let Some(caption) = caption {
+            str_vec.push(format!("<figcaption>{caption}</figcaption>"));
+        }
+        str_vec.push(String::from("</figure>"));
+        return str_vec.join("");
+    }
 
-            if peek_token.token_type == TokenType::LineBreak {
-                break;
-            }
+    fn inline_block(&mut self, content: &str) -> String {
+        return content.to_string();
+    }
 
-            if peek_token.token_type == TokenType::LBracket && self.lexer.peek_nth_token(i+1).token_type == TokenType::RParen {
-                //this is an actual link
-                is_link = true;
-                break;
-            }
+    fn text(&mut self, text: &str) -> String {
+        return text.to_string();
+    }
+
+    fn abbreviation(&mut self, abbr: &str, title: &str) -> String {
+        return format!("<abbr title=\"{}\">{abbr}</abbr>", escape_attribute(title));
+    }
 
-            i += 1;
-            peek_token = self.lexer.peek_nth_token(i);
+    fn link(&mut self, href: &str, content: &str) -> String {
+        return format!("<a href=\"{href}\">{content}</a>");
+    }
+
+    fn image(&mut self, image: &Image) -> String {
+        let mut html = format!("<img src=\"{}\" alt=\"{}\"", escape_attribute(&image.src), escape_attribute(&image.alt));
+        if let Some(title) = &image.title {
+            html.push_str(&format!(" title=\"{}\"", escape_attribute(title)));
+        }
+        if let Some(id) = &image.attributes.id {
+            html.push_str(&format!(" id=\"{}\"", escape_attribute(id)));
+        }
+        if !image.attributes.classes.is_empty() {
+            html.push_str(&format!(" class=\"{}\"", escape_attribute(&image.attributes.classes.join(" "))));
         }
+        for (key, value) in &image.attributes.pairs {
+            html.push_str(&format!(" {key}=\"{}\"", escape_attribute(value)));
+        }
+        html.push('>');
+        return html;
+    }
 
-        let mut res = String::default();
-        if is_link {
-            let mut next_token = self.lexer.next_token();
-            let mut title = String::default();
-            while next_token.token_type != TokenType::LBracket {
-                title.push_str(&next_token.literal);
-                next_token = self.lexer.next_token();
-            }
+    fn code_span(&mut self, code: &str) -> String {
+        return format!("<code>{}</code>", escape_html(code));
+    }
+}
 
-            let mut href = String::default();
-            self.lexer.next_token(); // (
-            next_token = self.lexer.next_token();
-            while next_token.token_type != TokenType::LParen {
-                href.push_str(&next_token.literal);
-                next_token = self.lexer.next_token();
-            }
-            res = format!("<a href=\"{href}\">{title}</a>");
-        } 
+fn escape_html(value: &str) -> String {
+    return value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+fn escape_attribute(value: &str) -> String {
+    return value.replace('&', "&amp;").replace('"', "&quot;");
+}
 
-        return res;
+#[cfg(test)]
+mod tests {
+    use crate::parsers::markdown::{Lexer, Extensions, Diagnostic, DiagnosticKind};
+
+    use super::HtmlGenerator;
+
+    #[test]
+    fn get_empty_document() {
+        let lexer = Lexer::new("").unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, "");
+        assert!(html_generator.get_warnings().is_empty());
     }
 
-    fn generate_image_html(&mut self) -> String {
-        let mut i = 1;
-        let mut peek_token = self.lexer.peek_nth_token(i);
-        let mut is_image = false;
-        loop {
-            if peek_token.token_type == TokenType::EOF {
-                break;
-            }
+    #[test]
+    fn get_code_block() {
+        let input = "Lorem ipsum\n\
+                     ```rust\n\
+                     # not a heading\n\
+                     1. <b>\n\
+                     ```\n\
+                     \n\
+                     After";
+        let expected = "<p>Lorem ipsum</p><pre><code class=\"language-rust\"># not a heading\n1. &lt;b&gt;</code></pre><p>After</p>";
 
-            if peek_token.token_type == TokenType::LineBreak {
-                break;
-            }
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
 
-            if peek_token.token_type == TokenType::LBracket && self.lexer.peek_nth_token(i+1).token_type == TokenType::RParen {
-                //this is an actual link
-                is_image = true;
-                break;
-            }
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert!(html_generator.get_warnings().is_empty());
+    }
 
-            i += 1;
-            peek_token = self.lexer.peek_nth_token(i);
-        }
+    #[test]
+    fn get_code_block_unclosed_fence_warns() {
+        let input = "# Code\n\
+                     ```\n\
+                     a";
+        let expected = "<h1>Code</h1><pre><code>a</code></pre>";
 
-        let mut res = String::from("!");
-        if is_image {
-            self.lexer.next_token(); // [
-            let mut next_token = self.lexer.next_token();
-            let mut alt_text = String::default();
-            while next_token.token_type != TokenType::LBracket {
-                alt_text.push_str(&next_token.literal);
-                next_token = self.lexer.next_token();
-            }
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
 
-            let mut href = String::default();
-            self.lexer.next_token(); // (
-            next_token = self.lexer.next_token();
-            while next_token.token_type == TokenType::Letter && (next_token.literal != " " && next_token.literal != ")") {
-                href.push_str(&next_token.literal);
-                next_token = self.lexer.next_token();
-            }
-            if next_token.literal == " " {
-                next_token = self.lexer.next_token(); // space
-            }
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings(), &[Diagnostic::new(DiagnosticKind::UnclosedFence, 2, 1)]);
+    }
 
-            let mut title = String::default();
-            while next_token.token_type != TokenType::LParen && next_token.token_type != TokenType::EOF {
-                title.push_str(&next_token.literal);
-                next_token = self.lexer.next_token();
-            }
-            if title.is_empty() {
-                res = format!("<img src=\"{href}\" alt=\"{alt_text}\">");
-            } else {
-                res = format!("<img src=\"{href}\" alt=\"{alt_text}\" title=\"{title}\">");
-            }
-        } 
+    #[test]
+    fn get_code_span_in_paragraph() {
+        let input = "Use `[a](b)` and `<br>`";
+        let expected = "<p>Use <code>[a](b)</code> and <code>&lt;br&gt;</code></p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
 
-        return res;
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::parsers::markdown::Lexer;
+    #[test]
+    fn get_link_without_closing_paren_warns() {
+        let input = "Lorem\n\
+                     \n\
+                     See [a](b";
+        let expected = "<p>Lorem</p><p>See [a](b</p>";
 
-    use super::HtmlGenerator;
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings(), &[Diagnostic::new(DiagnosticKind::UnclosedLink, 3, 5)]);
+    }
+
+    #[test]
+    fn get_link_without_closing_bracket_warns() {
+        let input = "[a";
+        let expected = "<p>[a</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings(), &[Diagnostic::new(DiagnosticKind::UnclosedLink, 1, 1)]);
+    }
+
+    #[test]
+    fn get_image_without_closing_paren_warns_once() {
+        let input = "![a](b";
+        let expected = "<p>![a](b</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings().len(), 1);
+        assert_eq!(html_generator.get_warnings()[0].kind, DiagnosticKind::UnclosedLink);
+    }
+
+    #[test]
+    fn get_text_in_brackets_is_kept() {
+        let input = "A [note] b!";
+        let expected = "<p>A [note] b!</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert!(html_generator.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn get_unordered_list_warns_unknown_construct() {
+        let input = "- a";
+        let expected = "<p>- a</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings(), &[Diagnostic::new(DiagnosticKind::UnknownConstruct("unordered list".to_string()), 1, 1)]);
+    }
+
+    #[test]
+    fn get_heading_level_7_warns_unknown_construct() {
+        let input = "####### He";
+        let expected = "<h6>He</h6>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(html_generator.get_warnings().len(), 1);
+    }
+
+    #[test]
+    fn get_emoji_shortcodes() {
+        let input = "# Launch :rocket:\n\
+                     Done :white_check_mark: at 10:30, `:rocket:` stays\n\
+                     \n\
+                     ```\n\
+                     :tada:\n\
+                     ```";
+        let expected = "<h1>Launch 🚀</h1><p>Done ✅ at 10:30, <code>:rocket:</code> stays</p><pre><code>:tada:</code></pre>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_heading_skips_code_spans() {
+        let input = "*[CSS]: Cascading Style Sheets\n\
+                     # Use `:rocket:` and `CSS` here :rocket:";
+        let expected = "<h1>Use <code>:rocket:</code> and <code>CSS</code> here 🚀</h1>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_paragraph_starting_with_code_span() {
+        let input = "`a` and `b`";
+        let expected = "<p><code>a</code> and <code>b</code></p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_emoji_shortcode_in_leading_code_span() {
+        let input = "`:rocket:` is code :rocket:";
+        let expected = "<p><code>:rocket:</code> is code 🚀</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_emoji_shortcodes_disabled() {
+        let input = "Launch :rocket:";
+        let expected = "<p>Launch :rocket:</p>";
+        let extensions = Extensions { emoji: false, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_definition_list() {
+        let input = "Term\n\
+                     : First definition\n\
+                     : Second definition";
+        let expected = "<dl><dt>Term</dt><dd>First definition</dd><dd>Second definition</dd></dl>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_definition_list_with_multiple_terms() {
+        let input = "Apple\n\
+                     : A fruit\n\
+                     \n\
+                     Rust\n\
+                     : A [language](https://rust-lang.org)\n\
+                     \n\
+                     Lorem ipsum";
+        let expected = "<dl><dt>Apple</dt><dd>A fruit</dd><dt>Rust</dt><dd>A <a href=\"https://rust-lang.org\">language</a></dd></dl><p>Lorem ipsum</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_definition_list_disabled() {
+        let input = "Term\n\
+                     : Definition";
+        let expected = "<p>Term: Definition</p>";
+        let extensions = Extensions { definition_lists: false, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_abbreviation() {
+        let input = "HTML and HTMLX in a HTML5 page\n\
+                     \n\
+                     *[HTML]: Hyper Text Markup Language";
+        let expected = "<p><abbr title=\"Hyper Text Markup Language\">HTML</abbr> and HTMLX in a HTML5 page</p>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_abbreviation_in_heading() {
+        let input = "*[CSS]: Cascading Style Sheets\n\
+                     # About CSS";
+        let expected = "<h1>About <abbr title=\"Cascading Style Sheets\">CSS</abbr></h1>";
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::new(lexer);
+
+        let result = html_generator.get_html().unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_abbreviation_disabled() {
+        let input = "HTML\n\
+                     \n\
+                     *[HTML]: Hyper Text Markup Language";
+        let extensions = Extensions { abbreviations: false, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).unwrap();
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+
+        let result = html_generator.get_html().unwrap();
+        assert!(result.starts_with("<p>HTML</p>"), "Abbreviation was applied");
+        assert!(!result.contains("<abbr"), "Abbreviation was applied");
+    }
+
+    #[test]
+    fn get_image_with_quoted_title() {
+        let input = "![a](b.png \"A title\")";
+        let expected = "<img src=\"b.png\" alt=\"a\" title=\"A title\">";
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::new(lexer);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_image_with_attributes_in_paragraph() {
+        let input = "a ![a](b.png){width=300 height=200 .wide loading=lazy} b";
+        let expected = "<p>a <img src=\"b.png\" alt=\"a\" class=\"wide\" width=\"300\" height=\"200\" loading=\"lazy\"> b</p>";
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::new(lexer);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_image_with_invalid_attributes_keeps_text() {
+        let input = "a ![a](b){width} b";
+        let expected = "<p>a <img src=\"b\" alt=\"a\">{width} b</p>";
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::new(lexer);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_standalone_image_as_figure() {
+        let input = "![a](b.png \"Caption\"){.wide}\n\
+                     \n\
+                     Lorem ipsum";
+        let expected = "<figure><img src=\"b.png\" alt=\"a\" title=\"Caption\" class=\"wide\"><figcaption>Caption</figcaption></figure><p>Lorem ipsum</p>";
+        let extensions = Extensions { figures: true, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_standalone_image_without_title_as_figure() {
+        let input = "![a](b.png)";
+        let expected = "<figure><img src=\"b.png\" alt=\"a\"></figure>";
+        let extensions = Extensions { figures: true, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn get_image_in_paragraph_is_not_figure() {
+        let input = "a ![a](b.png \"Caption\") b";
+        let expected = "<p>a <img src=\"b.png\" alt=\"a\" title=\"Caption\"> b</p>";
+        let extensions = Extensions { figures: true, ..Extensions::default() };
+
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+
+        let mut html_generator = HtmlGenerator::with_extensions(lexer, extensions);
+        let result = html_generator.get_html().expect("ERROR: Couldn't get html");
+        assert_eq!(result, expected);
+    }
 
     #[test]
     fn get_image_without_title_in_paragraph() {
@@ -1,5 +1,13 @@
-mod merge_page;
+mod renderer;
+mod generator;
 mod html;
+mod text;
+mod gemtext;
+mod template;
 
-pub use merge_page::MergePage;
-pub use html::HtmlGenerator;
+pub use renderer::{Renderer, Image};
+pub use generator::Generator;
+pub use html::{HtmlGenerator, HtmlRenderer};
+pub use text::TextRenderer;
+pub use gemtext::GemtextRenderer;
+pub use template::{Template, Templates, TemplateError, Context};
@@ -0,0 +1,31 @@
+use crate::parsers::markdown::Attributes;
+
+/// Output format for the markdown pipeline. The `Generator` parses the
+/// document and calls one method per element, passing content that has
+/// already been rendered by the same renderer. Other crates plug in their
+/// own with `Generator::with_renderer`, taking the `Lexer` and `Extensions`
+/// from `afskylia::parsers::markdown`.
+pub trait Renderer {
+    fn heading(&mut self, level: usize, content: &str) -> String;
+    fn paragraph(&mut self, content: &str) -> String;
+    fn ordered_list(&mut self, items: &[String]) -> String;
+    fn definition_list(&mut self, entries: &[(String, Vec<String>)]) -> String;
+    fn code_block(&mut self, language: &str, code: &str) -> String;
+    fn figure(&mut self, image: &Image, caption: Option<&str>) -> String;
+    /// Inline content that stands alone as a block, like a link on its own line.
+    fn inline_block(&mut self, content: &str) -> String;
+
+    fn text(&mut self, text: &str) -> String;
+    fn abbreviation(&mut self, abbr: &str, title: &str) -> String;
+    fn link(&mut self, href: &str, content: &str) -> String;
+    fn image(&mut self, image: &Image) -> String;
+    fn code_span(&mut self, code: &str) -> String;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub src: String,
+    pub alt: String,
+    pub title: Option<String>,
+    pub attributes: Attributes,
+}
@@ -0,0 +1,25 @@
+use std::fmt::Display;
+
+/// A template that couldn't be parsed or rendered. `name` is the layout it
+/// came from, e.g. `layouts/_single.html`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub name: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl TemplateError {
+    pub fn new(name: &str, line: usize, message: impl Into<String>) -> Self {
+        return TemplateError { name: name.to_string(), line, message: message.into() };
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            0 => write!(f, "{}: {}", self.name, self.message),
+            line => write!(f, "{}:{}: {}", self.name, line, self.message),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::parsers::metadata::{AfDateTime, Value};
+
+/// Every filter a template can use, checked when the template is parsed.
+pub(super) static FILTERS: [&str; 8] = ["date", "default", "escape", "join", "length", "lower", "truncate", "upper"];
+
+/// Applies the filter `name` to `value`. Only `default` accepts an undefined
+/// value.
+pub(super) fn apply(name: &str, value: Option<Value>, args: &[Value]) -> Result<Value, String> {
+    if name == "default" {
+        let fallback = args.first().cloned().unwrap_or(Value::String(String::default()));
+        return Ok(match value {
+            Some(Value::String(s)) if s.is_empty() => fallback,
+            Some(value) => value,
+            None => fallback,
+        });
+    }
+    let Some(value) = value else {
+        return Err(format!("filter \"{name}\" needs a value"));
+    };
+
+    return match name {
+        "date" => {
+            let format = string_arg(args, 0).unwrap_or("%Y-%m-%d");
+            let date = AfDateTime::parse(&value.to_string()).map_err(|e| format!("filter \"date\": {e}"))?;
+            Ok(Value::String(date.format(format)))
+        }
+        "escape" => Ok(Value::String(escape(&value.to_string()))),
+        "join" => match value {
+            Value::Array(items) => {
+                let separator = string_arg(args, 0).unwrap_or(", ");
+                Ok(Value::String(items.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(separator)))
+            }
+            _ => Err("filter \"join\" needs a list".to_string()),
+        },
+        "length" => match value {
+            Value::String(s) => Ok(Value::Integer(s.chars().count() as i64)),
+            Value::Array(items) => Ok(Value::Integer(items.len() as i64)),
+            Value::Table(table) => Ok(Value::Integer(table.len() as i64)),
+            _ => Err("filter \"length\" needs a string, list or table".to_string()),
+        },
+        "lower" => Ok(Value::String(value.to_string().to_lowercase())),
+        "truncate" => {
+            let Some(Value::Integer(length)) = args.first() else {
+                return Err("filter \"truncate\" needs a length, e.g. truncate(100)".to_string());
+            };
+            let text = value.to_string();
+            match text.chars().count() > *length as usize {
+                true => Ok(Value::String(format!("{}…", text.chars().take(*length as usize).collect::<String>().trim_end()))),
+                false => Ok(Value::String(text)),
+            }
+        }
+        "upper" => Ok(Value::String(value.to_string().to_uppercase())),
+        _ => Err(format!("unknown filter {name:?}")),
+    };
+}
+
+fn string_arg(args: &[Value], index: usize) -> Option<&str> {
+    return args.get(index).and_then(|x| x.as_str());
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+#[cfg(test)]
+mod filters_tests {
+    use claim::assert_err;
+
+    use crate::parsers::metadata::Value;
+
+    use super::apply;
+
+    fn string(s: &str) -> Value {
+        return Value::String(s.to_string());
+    }
+
+    #[test]
+    fn apply_filters() {
+        assert_eq!(apply("date", Some(string("2023-04-08T10:17:00")), &[string("%d.%m.%Y")]), Ok(string("08.04.2023")));
+        assert_eq!(apply("escape", Some(string("<a href=\"x\">")), &[]), Ok(string("&lt;a href=&quot;x&quot;&gt;")));
+        assert_eq!(apply("truncate", Some(string("Hello world")), &[Value::Integer(6)]), Ok(string("Hello…")));
+        assert_eq!(apply("truncate", Some(string("Hello")), &[Value::Integer(6)]), Ok(string("Hello")));
+        assert_eq!(apply("upper", Some(string("rust")), &[]), Ok(string("RUST")));
+        assert_eq!(apply("length", Some(Value::Array(vec![string("a"), string("b")])), &[]), Ok(Value::Integer(2)));
+        assert_eq!(apply("join", Some(Value::Array(vec![string("a"), string("b")])), &[string(" / ")]), Ok(string("a / b")));
+        assert_eq!(apply("default", None, &[string("none")]), Ok(string("none")));
+        assert_eq!(apply("default", Some(string("set")), &[string("none")]), Ok(string("set")));
+    }
+
+    #[test]
+    fn apply_invalid_filter_input_is_error() {
+        assert_err!(apply("date", Some(string("yesterday")), &[]));
+        assert_err!(apply("truncate", Some(string("Hello")), &[]));
+        assert_err!(apply("upper", None, &[]));
+        assert_err!(apply("join", Some(string("a")), &[]));
+    }
+}
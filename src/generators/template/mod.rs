@@ -0,0 +1,237 @@
+mod error;
+mod filters;
+mod parser;
+mod render;
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::Value;
+
+pub use self::error::TemplateError;
+
+use self::parser::Node;
+use self::render::{Env, Scope, render_template};
+
+/// A parsed layout. Supports `{{ page.title | upper }}` output with filters,
+/// `{% if %}`/`{% elif %}`/`{% else %}`/`{% endif %}`,
+/// `{% for tag in page.tags %}`/`{% endfor %}` and `{# comments #}`.
+///
+/// Layouts can share markup through `{% include "nav.html" key=value %}`,
+/// which renders `layouts/partials/nav.html` with the extra variables, or by
+/// `{% extends "_base.html" %}` and replacing its `{% block main %}`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    name: String,
+    nodes: Vec<Node>,
+    /// The layout this one extends, and the line saying so
+    parent: Option<(String, usize)>,
+}
+
+/// A template on its own can't include or extend anything.
+static NO_TEMPLATES: Templates = Templates { templates: BTreeMap::new() };
+
+impl Template {
+    /// `name` is used in error messages, e.g. `layouts/index.html`.
+    pub fn parse(name: &str, source: &str) -> Result<Template, TemplateError> {
+        let nodes = parser::parse(name, source)?;
+        let parent = nodes.iter().find_map(|node| match node {
+            Node::Extends { name, line } => Some((name.clone(), *line)),
+            _ => None,
+        });
+        return Ok(Template { name: name.to_string(), nodes, parent });
+    }
+
+    pub fn get_name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// The key of the layout this one extends, e.g. `_base.html`.
+    pub fn get_parent(&self) -> Option<&str> {
+        return self.parent.as_ref().map(|(key, _)| key.as_str());
+    }
+
+    pub fn render(&self, context: &Context) -> Result<String, TemplateError> {
+        return NO_TEMPLATES.render_template(self, context);
+    }
+}
+
+/// Every layout of a site by its path in `layouts/`, e.g. `partials/nav.html`,
+/// so they can include and extend each other.
+#[derive(Debug, Default)]
+pub struct Templates {
+    templates: BTreeMap<String, Template>,
+}
+
+impl Templates {
+    pub fn new() -> Self {
+        return Templates::default();
+    }
+
+    pub fn insert(&mut self, key: &str, template: Template) {
+        self.templates.insert(key.to_string(), template);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Template> {
+        return self.templates.get(key);
+    }
+
+    pub fn render(&self, key: &str, context: &Context) -> Result<String, TemplateError> {
+        let Some(template) = self.get(key) else {
+            return Err(TemplateError::new(key, 0, "no such layout"));
+        };
+        return self.render_template(template, context);
+    }
+
+    fn render_template(&self, template: &Template, context: &Context) -> Result<String, TemplateError> {
+        let mut env = Env { templates: self, stack: vec![&template.name] };
+        let mut output = String::new();
+        render_template(&mut env, template, &Scope::Root(context), &mut output)?;
+        return Ok(output);
+    }
+}
+
+/// The top level variables a template is rendered with. Values that are the
+/// same for every page, like `site`, can be borrowed instead of copied.
+#[derive(Debug, Default)]
+pub struct Context<'a> {
+    vars: BTreeMap<String, Cow<'a, Value>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new() -> Self {
+        return Context::default();
+    }
+
+    pub fn insert(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), Cow::Owned(value));
+    }
+
+    pub fn insert_ref(&mut self, name: &str, value: &'a Value) {
+        self.vars.insert(name.to_string(), Cow::Borrowed(value));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        return self.vars.get(name).map(|x| x.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod template_tests {
+    use std::collections::BTreeMap;
+
+    use claim::assert_err;
+
+    use crate::parsers::metadata::Value;
+
+    use super::{Context, Template, Templates};
+
+    fn string(s: &str) -> Value {
+        return Value::String(s.to_string());
+    }
+
+    fn context() -> Context<'static> {
+        let page = Value::Table(BTreeMap::from([
+            ("title".to_string(), string("Hello & welcome")),
+            ("date".to_string(), string("2023-04-08T10:17:00")),
+            ("tags".to_string(), Value::Array(vec![string("rust"), string("web")])),
+            ("draft".to_string(), Value::Boolean(false)),
+        ]));
+        let site = Value::Table(BTreeMap::from([
+            ("params".to_string(), Value::Table(BTreeMap::from([("author".to_string(), string("Jane"))]))),
+        ]));
+
+        let mut context = Context::new();
+        context.insert("page", page);
+        context.insert("site", site);
+        return context;
+    }
+
+    #[test]
+    fn render_variables_and_filters() {
+        let template = Template::parse("test", "<h1>{{ page.title | escape }}</h1><p>{{ site.params.author | upper }}, {{ page.date | date(\"%d.%m.%Y\") }}</p>{# hidden #}")
+            .expect("ERROR: Couldn't parse template");
+
+        let output = template.render(&context()).expect("ERROR: Couldn't render template");
+
+        assert_eq!(output, "<h1>Hello &amp; welcome</h1><p>JANE, 08.04.2023</p>");
+    }
+
+    #[test]
+    fn render_if_and_for() {
+        let source = "{% for tag in page.tags %}{% if not loop.first %}, {% endif %}#{{ loop.index }} {{ tag }}{% endfor %}\
+                      {% if page.draft %} draft{% elif page.tags | length > 1 %} many{% else %} one{% endif %}\
+                      {% if page.missing %} missing{% endif %}\
+                      {% for param in site.params %} {{ param.key }}={{ param.value }}{% endfor %}";
+        let template = Template::parse("test", source).expect("ERROR: Couldn't parse template");
+
+        let output = template.render(&context()).expect("ERROR: Couldn't render template");
+
+        assert_eq!(output, "#1 rust, #2 web many author=Jane");
+    }
+
+    #[test]
+    fn render_undefined_variable_is_error_with_line() {
+        let template = Template::parse("layouts/index.html", "<h1>\n{{ page.subtitle }}</h1>").expect("ERROR: Couldn't parse template");
+
+        let error = template.render(&context()).expect_err("ERROR: Undefined variable rendered");
+
+        assert_eq!(error.to_string(), "layouts/index.html:2: undefined variable \"page.subtitle\"");
+        let template = Template::parse("test", "{{ page.subtitle | default(page.title) }}").expect("ERROR: Couldn't parse template");
+        assert_eq!(template.render(&context()), Ok("Hello & welcome".to_string()));
+    }
+
+    fn templates(layouts: &[(&str, &str)]) -> Templates {
+        let mut templates = Templates::new();
+        for (key, source) in layouts {
+            templates.insert(key, Template::parse(&format!("layouts/{key}"), source).expect("ERROR: Couldn't parse template"));
+        }
+        return templates;
+    }
+
+    #[test]
+    fn render_include_with_arguments() {
+        let templates = templates(&[
+            ("index.html", "{% include \"title.html\" level=2, text=page.title | upper %}{% for tag in page.tags %}{% include \"tag.html\" %}{% endfor %}"),
+            ("partials/title.html", "<h{{ level }}>{{ text }}</h{{ level }}>"),
+            ("partials/tag.html", "#{{ tag }}"),
+        ]);
+
+        let output = templates.render("index.html", &context()).expect("ERROR: Couldn't render template");
+
+        assert_eq!(output, "<h2>HELLO & WELCOME</h2>#rust#web");
+    }
+
+    #[test]
+    fn render_extends_replaces_blocks() {
+        let templates = templates(&[
+            ("_base.html", "<head>{% block head %}<title>Site</title>{% endblock %}</head><main>{% block main %}{% endblock %}</main>{% block footer %}<footer>{{ site.params.author }}</footer>{% endblock %}"),
+            ("_single.html", "{% extends \"_base.html\" %}{% block main %}<article>{% block article %}{% endblock %}</article>{% endblock %}"),
+            ("post.html", "{% extends \"_single.html\" %}\n{% block head %}<title>{{ page.title }}</title>{% endblock %}{% block article %}{{ page.date | date }}{% endblock %}"),
+        ]);
+
+        let output = templates.render("post.html", &context()).expect("ERROR: Couldn't render template");
+
+        assert_eq!(output, "<head><title>Hello & welcome</title></head><main><article>2023-04-08</article></main><footer>Jane</footer>");
+        assert_eq!(templates.get("post.html").and_then(|x| x.get_parent()), Some("_single.html"));
+    }
+
+    #[test]
+    fn render_include_and_extends_cycles_are_errors() {
+        let templates = templates(&[
+            ("index.html", "{% include \"a.html\" %}"),
+            ("partials/a.html", "{% include \"b.html\" %}"),
+            ("partials/b.html", "\n{% include \"a.html\" %}"),
+            ("one.html", "{% extends \"two.html\" %}"),
+            ("two.html", "{% extends \"one.html\" %}"),
+            ("orphan.html", "{% include \"missing.html\" %}"),
+        ]);
+
+        let include_error = templates.render("index.html", &context()).expect_err("ERROR: Include cycle rendered");
+        let extends_error = templates.render("one.html", &context()).expect_err("ERROR: Extends cycle rendered");
+
+        assert_eq!(include_error.to_string(), "layouts/partials/b.html:2: includes itself: layouts/index.html -> layouts/partials/a.html -> layouts/partials/b.html -> layouts/partials/a.html");
+        assert_eq!(extends_error.to_string(), "layouts/two.html:1: extends itself: layouts/one.html -> layouts/two.html -> layouts/one.html");
+        assert_err!(templates.render("orphan.html", &context()));
+    }
+}
@@ -0,0 +1,537 @@
+use crate::parsers::metadata::Value;
+
+use super::error::TemplateError;
+use super::filters::FILTERS;
+
+/// A parsed piece of a template.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Node {
+    Text(String),
+    /// `{{ expr }}`
+    Output { expr: Expr, line: usize },
+    /// `{% if %}`, any `{% elif %}` and the `{% else %}` body
+    If { branches: Vec<(Expr, Vec<Node>)>, otherwise: Vec<Node>, line: usize },
+    /// `{% for var in expr %}`
+    For { var: String, iterable: Expr, body: Vec<Node>, line: usize },
+    /// `{% include "nav.html" title=page.title %}`, from `layouts/partials/`
+    Include { name: String, args: Vec<(String, Expr)>, line: usize },
+    /// `{% block main %}`, which a layout extending this one can replace
+    Block { name: String, body: Vec<Node>, line: usize },
+    /// `{% extends "_base.html" %}`, only allowed before anything else
+    Extends { name: String, line: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Expr {
+    /// `page.params.hero`
+    Path(Vec<String>),
+    Literal(Value),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+    /// `expr | name(args)`
+    Filter { expr: Box<Expr>, name: String, args: Vec<Expr> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A `{{ }}`, `{% %}` or `{# #}` tag, or the text between them.
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Output(&'a str, usize),
+    Block(&'a str, usize),
+}
+
+pub(super) fn parse(name: &str, source: &str) -> Result<Vec<Node>, TemplateError> {
+    let segments = split_segments(name, source)?;
+    let mut parser = Parser { name, segments, position: 0, blocks: Vec::new() };
+    let (nodes, end) = parser.parse_nodes(&[])?;
+    if let Some((tag, line)) = end {
+        return Err(TemplateError::new(name, line, format!("unexpected {{% {tag} %}}")));
+    }
+    return Ok(nodes);
+}
+
+fn split_segments<'a>(name: &str, source: &'a str) -> Result<Vec<Segment<'a>>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut line = 1;
+    let mut text_start = 0;
+    let mut search = 0;
+
+    while let Some(found) = source[search..].find('{') {
+        let start = search + found;
+        let closing = match source[start + 1..].chars().next() {
+            Some('{') => "}}",
+            Some('%') => "%}",
+            Some('#') => "#}",
+            // A lone brace, e.g. in inline css, is text
+            _ => {
+                search = start + 1;
+                continue;
+            }
+        };
+
+        let text = &source[text_start..start];
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        line += text.matches('\n').count();
+
+        let inner_start = start + 2;
+        let Some(end) = source[inner_start..].find(closing) else {
+            return Err(TemplateError::new(name, line, format!("unclosed {}, expected \"{closing}\"", &source[start..inner_start])));
+        };
+        let inner = &source[inner_start..inner_start + end];
+        match closing {
+            "}}" => segments.push(Segment::Output(inner.trim(), line)),
+            "%}" => segments.push(Segment::Block(inner.trim(), line)),
+            _ => {},
+        }
+        line += inner.matches('\n').count();
+        text_start = inner_start + end + closing.len();
+        search = text_start;
+    }
+    if text_start < source.len() {
+        segments.push(Segment::Text(&source[text_start..]));
+    }
+
+    return Ok(segments);
+}
+
+/// The block tag that ended a run of nodes, and its line.
+type Terminator = Option<(String, usize)>;
+
+struct Parser<'a> {
+    name: &'a str,
+    segments: Vec<Segment<'a>>,
+    position: usize,
+    /// Every block defined so far, as a name can only be used once
+    blocks: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses nodes until one of the `terminators` block tags, which is
+    /// returned with its line, or the end of the template.
+    fn parse_nodes(&mut self, terminators: &[&str]) -> Result<(Vec<Node>, Terminator), TemplateError> {
+        let mut nodes = Vec::new();
+
+        while let Some(segment) = self.segments.get(self.position) {
+            self.position += 1;
+            match *segment {
+                Segment::Text(text) => nodes.push(Node::Text(text.to_string())),
+                Segment::Output(expr, line) => nodes.push(Node::Output { expr: self.parse_expr(expr, line)?, line }),
+                Segment::Block(tag, line) => {
+                    let keyword = tag.split_whitespace().next().unwrap_or_default();
+                    if terminators.contains(&keyword) {
+                        return Ok((nodes, Some((tag.to_string(), line))));
+                    }
+                    match keyword {
+                        "if" => nodes.push(self.parse_if(tag, line)?),
+                        "for" => nodes.push(self.parse_for(tag, line)?),
+                        "include" => nodes.push(self.parse_include(tag, line)?),
+                        "block" => nodes.push(self.parse_block(tag, line)?),
+                        "extends" => {
+                            let first = terminators.is_empty() && nodes.iter().all(|x| matches!(x, Node::Text(text) if text.trim().is_empty()));
+                            if !first {
+                                return Err(TemplateError::new(self.name, line, "{% extends %} has to come before anything else"));
+                            }
+                            nodes.push(self.parse_extends(tag, line)?);
+                        }
+                        "" => return Err(TemplateError::new(self.name, line, "empty {% %} tag")),
+                        _ => return Err(TemplateError::new(self.name, line, format!("unexpected {{% {tag} %}}"))),
+                    }
+                }
+            }
+        }
+
+        return Ok((nodes, None));
+    }
+
+    fn parse_if(&mut self, tag: &str, line: usize) -> Result<Node, TemplateError> {
+        let mut branches = Vec::new();
+        let mut condition = self.parse_expr(&tag["if".len()..], line)?;
+
+        loop {
+            let (body, end) = self.parse_nodes(&["elif", "else", "endif"])?;
+            let Some((end_tag, end_line)) = end else {
+                return Err(TemplateError::new(self.name, line, "{% if %} is never closed with {% endif %}"));
+            };
+            branches.push((condition, body));
+
+            match end_tag.split_whitespace().next() {
+                Some("elif") => condition = self.parse_expr(&end_tag["elif".len()..], end_line)?,
+                Some("else") => {
+                    let (otherwise, end) = self.parse_nodes(&["endif"])?;
+                    if end.is_none() {
+                        return Err(TemplateError::new(self.name, line, "{% if %} is never closed with {% endif %}"));
+                    }
+                    return Ok(Node::If { branches, otherwise, line });
+                }
+                _ => return Ok(Node::If { branches, otherwise: Vec::new(), line }),
+            }
+        }
+    }
+
+    fn parse_for(&mut self, tag: &str, line: usize) -> Result<Node, TemplateError> {
+        let rest = tag["for".len()..].trim();
+        let (var, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let (keyword, iterable) = rest.trim_start().split_once(char::is_whitespace).unwrap_or((rest.trim_start(), ""));
+        if keyword != "in" || iterable.trim().is_empty() {
+            return Err(TemplateError::new(self.name, line, "expected {% for item in list %}"));
+        }
+        if !var.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(TemplateError::new(self.name, line, format!("invalid loop variable {var:?}")));
+        }
+        let iterable = self.parse_expr(iterable, line)?;
+
+        let (body, end) = self.parse_nodes(&["endfor"])?;
+        if end.is_none() {
+            return Err(TemplateError::new(self.name, line, "{% for %} is never closed with {% endfor %}"));
+        }
+
+        return Ok(Node::For { var: var.to_string(), iterable, body, line });
+    }
+
+    fn parse_include(&self, tag: &str, line: usize) -> Result<Node, TemplateError> {
+        let tokens = tokenize(&tag["include".len()..]).map_err(|e| TemplateError::new(self.name, line, e))?;
+        let mut parser = ExprParser { tokens, position: 0 };
+        let Some(Token::Literal(Value::String(name))) = parser.next() else {
+            return Err(TemplateError::new(self.name, line, "expected {% include \"name.html\" key=value %}"));
+        };
+
+        let mut args = Vec::new();
+        while let Some(token) = parser.next() {
+            let key = match token {
+                Token::Ident(key) if !key.contains('.') && parser.peek() == Some(&Token::Assign) => key,
+                token => return Err(TemplateError::new(self.name, line, format!("unexpected {token:?} in include, expected key=value"))),
+            };
+            parser.position += 1;
+            let value = parser.parse_or().map_err(|e| TemplateError::new(self.name, line, e))?;
+            args.push((key, value));
+            if parser.peek() == Some(&Token::Comma) {
+                parser.position += 1;
+            }
+        }
+
+        return Ok(Node::Include { name, args, line });
+    }
+
+    fn parse_block(&mut self, tag: &str, line: usize) -> Result<Node, TemplateError> {
+        let name = tag["block".len()..].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(TemplateError::new(self.name, line, format!("invalid block name {name:?}")));
+        }
+        if self.blocks.iter().any(|x| x == name) {
+            return Err(TemplateError::new(self.name, line, format!("block \"{name}\" is defined twice")));
+        }
+        self.blocks.push(name.to_string());
+
+        let (body, end) = self.parse_nodes(&["endblock"])?;
+        let Some((end_tag, end_line)) = end else {
+            return Err(TemplateError::new(self.name, line, format!("{{% block {name} %}} is never closed with {{% endblock %}}")));
+        };
+        let end_name = end_tag["endblock".len()..].trim();
+        if !end_name.is_empty() && end_name != name {
+            return Err(TemplateError::new(self.name, end_line, format!("{{% {end_tag} %}} closes {{% block {name} %}}")));
+        }
+
+        return Ok(Node::Block { name: name.to_string(), body, line });
+    }
+
+    fn parse_extends(&self, tag: &str, line: usize) -> Result<Node, TemplateError> {
+        let tokens = tokenize(&tag["extends".len()..]).map_err(|e| TemplateError::new(self.name, line, e))?;
+        return match tokens.as_slice() {
+            [Token::Literal(Value::String(name))] => Ok(Node::Extends { name: name.clone(), line }),
+            _ => Err(TemplateError::new(self.name, line, "expected {% extends \"name.html\" %}")),
+        };
+    }
+
+    fn parse_expr(&self, input: &str, line: usize) -> Result<Expr, TemplateError> {
+        let tokens = tokenize(input).map_err(|e| TemplateError::new(self.name, line, e))?;
+        let mut parser = ExprParser { tokens, position: 0 };
+        let expr = parser.parse_or().map_err(|e| TemplateError::new(self.name, line, e))?;
+        if let Some(token) = parser.tokens.get(parser.position) {
+            return Err(TemplateError::new(self.name, line, format!("unexpected {token:?} in expression")));
+        }
+        return Ok(expr);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Value),
+    Op(CompareOp),
+    Pipe,
+    /// `=` between an include argument and its value
+    Assign,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '|' => { tokens.push(Token::Pipe); i += 1; }
+            '(' => { tokens.push(Token::LParen); i += 1; }
+            ')' => { tokens.push(Token::RParen); i += 1; }
+            ',' => { tokens.push(Token::Comma); i += 1; }
+            '=' | '!' | '<' | '>' => {
+                let op = match (c, next) {
+                    ('=', Some('=')) => CompareOp::Eq,
+                    ('!', Some('=')) => CompareOp::Ne,
+                    ('<', Some('=')) => CompareOp::Le,
+                    ('>', Some('=')) => CompareOp::Ge,
+                    ('<', _) => CompareOp::Lt,
+                    ('>', _) => CompareOp::Gt,
+                    ('=', _) => {
+                        tokens.push(Token::Assign);
+                        i += 1;
+                        continue;
+                    }
+                    _ => return Err(format!("unexpected {c:?} in expression")),
+                };
+                i += if matches!(op, CompareOp::Lt | CompareOp::Gt) { 1 } else { 2 };
+                tokens.push(Token::Op(op));
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..].iter().position(|x| *x == c).ok_or("unclosed string in expression")?;
+                tokens.push(Token::Literal(Value::String(chars[i + 1..i + 1 + end].iter().collect())));
+                i += end + 2;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|x| x.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number = chars[start..i].iter().collect::<String>();
+                let value = match number.contains('.') {
+                    true => Value::Float(number.parse().map_err(|_| format!("invalid number {number:?}"))?),
+                    false => Value::Integer(number.parse().map_err(|_| format!("invalid number {number:?}"))?),
+                };
+                tokens.push(Token::Literal(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("unexpected {c:?} in expression")),
+        }
+    }
+
+    return Ok(tokens);
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.position);
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        return token;
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        return matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword);
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.position += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        return Ok(expr);
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+        while self.is_keyword("and") {
+            self.position += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        return Ok(expr);
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.is_keyword("not") {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        return self.parse_compare();
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, String> {
+        let expr = self.parse_filtered()?;
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.position += 1;
+            return Ok(Expr::Compare(Box::new(expr), op, Box::new(self.parse_filtered()?)));
+        }
+        return Ok(expr);
+    }
+
+    fn parse_filtered(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_primary()?;
+        while self.peek() == Some(&Token::Pipe) {
+            self.position += 1;
+            let name = match self.next() {
+                Some(Token::Ident(name)) => name,
+                _ => return Err("expected a filter name after '|'".to_string()),
+            };
+            if !FILTERS.contains(&name.as_str()) {
+                return Err(format!("unknown filter {name:?}"));
+            }
+
+            let mut args = Vec::new();
+            if self.peek() == Some(&Token::LParen) {
+                self.position += 1;
+                while self.peek() != Some(&Token::RParen) {
+                    args.push(self.parse_or()?);
+                    match self.next() {
+                        Some(Token::Comma) => {},
+                        Some(Token::RParen) => { self.position -= 1; }
+                        _ => return Err(format!("expected ',' or ')' in arguments to {name:?}")),
+                    }
+                }
+                self.position += 1;
+            }
+
+            expr = Expr::Filter { expr: Box::new(expr), name, args };
+        }
+        return Ok(expr);
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        return match self.next() {
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Expr::Literal(Value::Boolean(true))),
+                "false" => Ok(Expr::Literal(Value::Boolean(false))),
+                _ if ident.split('.').any(|x| x.is_empty()) => Err(format!("invalid variable {ident:?}")),
+                _ => Ok(Expr::Path(ident.split('.').map(|x| x.to_string()).collect())),
+            },
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("expected ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {token:?} in expression")),
+            None => Err("expected an expression".to_string()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod parser_tests {
+    use claim::assert_err;
+
+    use crate::parsers::metadata::Value;
+
+    use super::{CompareOp, Expr, Node, parse};
+
+    fn path(path: &str) -> Expr {
+        return Expr::Path(path.split('.').map(|x| x.to_string()).collect());
+    }
+
+    #[test]
+    fn parse_text_output_and_comment() {
+        let nodes = parse("test", "<h1>{{ page.title | upper }}</h1>{# note #}<style>a { color: red }</style>").expect("ERROR: Couldn't parse template");
+
+        assert_eq!(nodes, vec![
+            Node::Text("<h1>".to_string()),
+            Node::Output { expr: Expr::Filter { expr: Box::new(path("page.title")), name: "upper".to_string(), args: vec![] }, line: 1 },
+            Node::Text("</h1>".to_string()),
+            Node::Text("<style>a { color: red }</style>".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn parse_if_elif_else_and_for() {
+        let source = "{% for tag in page.tags %}\n\
+                      {% if loop.first and tag != \"rust\" %}a{% elif not tag %}b{% else %}c{% endif %}\n\
+                      {% endfor %}";
+
+        let nodes = parse("test", source).expect("ERROR: Couldn't parse template");
+
+        let Node::For { var, body, .. } = &nodes[0] else { panic!("ERROR: Not a for loop") };
+        assert_eq!(var, "tag");
+        let Node::If { branches, otherwise, line } = &body[1] else { panic!("ERROR: Not an if") };
+        assert_eq!(*line, 2);
+        assert_eq!(branches[0].0, Expr::And(
+            Box::new(path("loop.first")),
+            Box::new(Expr::Compare(Box::new(path("tag")), CompareOp::Ne, Box::new(Expr::Literal(Value::String("rust".to_string()))))),
+        ));
+        assert_eq!(branches[1].0, Expr::Not(Box::new(path("tag"))));
+        assert_eq!(otherwise, &vec![Node::Text("c".to_string())]);
+    }
+
+    #[test]
+    fn parse_extends_block_and_include() {
+        let source = "\n{% extends \"_base.html\" %}\n{% block main %}{% include \"nav.html\" active=page.url, title=\"Home\" %}{% endblock main %}";
+
+        let nodes = parse("test", source).expect("ERROR: Couldn't parse template");
+
+        assert_eq!(nodes[1], Node::Extends { name: "_base.html".to_string(), line: 2 });
+        assert_eq!(nodes[3], Node::Block {
+            name: "main".to_string(),
+            body: vec![Node::Include {
+                name: "nav.html".to_string(),
+                args: vec![("active".to_string(), path("page.url")), ("title".to_string(), Expr::Literal(Value::String("Home".to_string())))],
+                line: 3,
+            }],
+            line: 3,
+        });
+    }
+
+    #[test]
+    fn parse_invalid_inheritance_is_error() {
+        assert_err!(parse("test", "<p></p>{% extends \"_base.html\" %}"));
+        assert_err!(parse("test", "{% extends _base %}"));
+        assert_err!(parse("test", "{% block main %}{% endblock %}{% block main %}{% endblock %}"));
+        assert_err!(parse("test", "{% block main %}{% endblock footer %}"));
+        assert_err!(parse("test", "{% block main %}"));
+        assert_err!(parse("test", "{% include \"nav.html\" page.title %}"));
+    }
+
+    #[test]
+    fn parse_errors_have_line() {
+        let error = parse("layouts/index.html", "<p>\n{{ page.title | shout }}</p>").expect_err("ERROR: Unknown filter parsed");
+        assert_eq!(error.to_string(), "layouts/index.html:2: unknown filter \"shout\"");
+
+        assert_err!(parse("test", "{% if page.title %}unclosed"));
+        assert_err!(parse("test", "{% endfor %}"));
+        assert_err!(parse("test", "{{ page.title"));
+        assert_err!(parse("test", "{% for in pages %}{% endfor %}"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::Value;
+
+use super::{Context, Template, Templates};
+use super::error::TemplateError;
+use super::filters;
+use super::parser::{CompareOp, Expr, Node};
+
+/// The variables visible at a point in a template: the context, plus one
+/// variable for each `for` loop around it.
+pub(super) enum Scope<'a> {
+    Root(&'a Context<'a>),
+    Var { name: &'a str, value: &'a Value, parent: &'a Scope<'a> },
+    /// The arguments of an include
+    Vars { vars: &'a BTreeMap<String, Value>, parent: &'a Scope<'a> },
+}
+
+impl<'a> Scope<'a> {
+    fn get(&self, name: &str) -> Option<&'a Value> {
+        return match self {
+            Scope::Root(context) => context.get(name),
+            Scope::Var { name: var, value, .. } if *var == name => Some(value),
+            Scope::Var { parent, .. } => parent.get(name),
+            Scope::Vars { vars, parent } => vars.get(name).or_else(|| parent.get(name)),
+        };
+    }
+}
+
+/// Block bodies by name, with the name of the template each one came from.
+type Blocks<'t> = BTreeMap<&'t str, (&'t str, &'t [Node])>;
+
+/// What rendering can reach besides the variables: the other layouts, and the
+/// templates currently being rendered, to stop includes that never end.
+pub(super) struct Env<'t> {
+    pub(super) templates: &'t Templates,
+    pub(super) stack: Vec<&'t str>,
+}
+
+/// Renders a template, or if it extends another, the outermost template with
+/// the blocks of every template in between.
+pub(super) fn render_template<'t>(env: &mut Env<'t>, template: &'t Template, scope: &Scope, output: &mut String) -> Result<(), TemplateError> {
+    let mut blocks = Blocks::new();
+    let mut chain = vec![template.name.as_str()];
+    let mut current = template;
+
+    loop {
+        collect_blocks(&current.name, &current.nodes, &mut blocks);
+        let Some((parent_key, line)) = &current.parent else {
+            break;
+        };
+        let Some(parent) = env.templates.get(parent_key) else {
+            return Err(TemplateError::new(&current.name, *line, format!("can't extend \"{parent_key}\", it doesn't exist")));
+        };
+        let cycle = chain.contains(&parent.name.as_str());
+        chain.push(&parent.name);
+        if cycle {
+            return Err(TemplateError::new(&current.name, *line, format!("extends itself: {}", chain.join(" -> "))));
+        }
+        current = parent;
+    }
+
+    return render_nodes(env, &current.name, &current.nodes, &blocks, scope, output);
+}
+
+/// Adds the blocks of a template that aren't already replaced by one
+/// extending it.
+fn collect_blocks<'t>(name: &'t str, nodes: &'t [Node], blocks: &mut Blocks<'t>) {
+    for node in nodes {
+        match node {
+            Node::Block { name: block, body, .. } => {
+                blocks.entry(block).or_insert((name, body));
+                collect_blocks(name, body, blocks);
+            }
+            Node::If { branches, otherwise, .. } => {
+                for (_, body) in branches {
+                    collect_blocks(name, body, blocks);
+                }
+                collect_blocks(name, otherwise, blocks);
+            }
+            Node::For { body, .. } => collect_blocks(name, body, blocks),
+            _ => {},
+        }
+    }
+}
+
+fn render_nodes<'t>(env: &mut Env<'t>, name: &'t str, nodes: &'t [Node], blocks: &Blocks<'t>, scope: &Scope, output: &mut String) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Output { expr, line } => {
+                let value = eval(expr, scope).map_err(|e| TemplateError::new(name, *line, e))?;
+                match value {
+                    Some(value) => output.push_str(&value.to_string()),
+                    None => return Err(TemplateError::new(name, *line, format!("undefined variable \"{}\"", describe(expr)))),
+                }
+            }
+            Node::If { branches, otherwise, line } => {
+                let mut body = otherwise;
+                for (condition, branch) in branches {
+                    let value = eval(condition, scope).map_err(|e| TemplateError::new(name, *line, e))?;
+                    if is_truthy(value.as_deref()) {
+                        body = branch;
+                        break;
+                    }
+                }
+                render_nodes(env, name, body, blocks, scope, output)?;
+            }
+            Node::For { var, iterable, body, line } => {
+                let value = eval(iterable, scope).map_err(|e| TemplateError::new(name, *line, e))?;
+                let items = match value.as_deref() {
+                    Some(Value::Array(items)) => Cow::Borrowed(items.as_slice()),
+                    // A table loops over its entries as `item.key` and `item.value`
+                    Some(Value::Table(table)) => Cow::Owned(table.iter().map(|(key, value)| {
+                        Value::Table(BTreeMap::from([("key".to_string(), Value::String(key.clone())), ("value".to_string(), value.clone())]))
+                    }).collect()),
+                    None => Cow::Owned(Vec::new()),
+                    Some(_) => return Err(TemplateError::new(name, *line, format!("can't loop over \"{}\", it isn't a list", describe(iterable)))),
+                };
+
+                for (i, item) in items.iter().enumerate() {
+                    let loop_value = Value::Table(BTreeMap::from([
+                        ("index".to_string(), Value::Integer(i as i64 + 1)),
+                        ("first".to_string(), Value::Boolean(i == 0)),
+                        ("last".to_string(), Value::Boolean(i + 1 == items.len())),
+                    ]));
+                    let loop_scope = Scope::Var { name: "loop", value: &loop_value, parent: scope };
+                    let item_scope = Scope::Var { name: var, value: item, parent: &loop_scope };
+                    render_nodes(env, name, body, blocks, &item_scope, output)?;
+                }
+            }
+            Node::Include { name: partial, args, line } => {
+                let Some(template) = env.templates.get(&format!("partials/{partial}")) else {
+                    return Err(TemplateError::new(name, *line, format!("can't include \"{partial}\", it isn't in partials")));
+                };
+                if env.stack.contains(&template.name.as_str()) {
+                    return Err(TemplateError::new(name, *line, format!("includes itself: {} -> {}", env.stack.join(" -> "), template.name)));
+                }
+
+                let mut vars = BTreeMap::new();
+                for (arg, expr) in args {
+                    let value = eval(expr, scope).map_err(|e| TemplateError::new(name, *line, e))?;
+                    let Some(value) = value else {
+                        return Err(TemplateError::new(name, *line, format!("undefined variable \"{}\"", describe(expr))));
+                    };
+                    vars.insert(arg.clone(), value.into_owned());
+                }
+
+                env.stack.push(&template.name);
+                render_template(env, template, &Scope::Vars { vars: &vars, parent: scope }, output)?;
+                env.stack.pop();
+            }
+            Node::Block { name: block, body, .. } => {
+                let (source, body) = blocks.get(block.as_str()).copied().unwrap_or((name, body));
+                render_nodes(env, source, body, blocks, scope, output)?;
+            }
+            Node::Extends { .. } => {},
+        }
+    }
+
+    return Ok(());
+}
+
+/// Evaluates an expression, `None` meaning an undefined variable.
+fn eval<'a>(expr: &Expr, scope: &Scope<'a>) -> Result<Option<Cow<'a, Value>>, String> {
+    return match expr {
+        Expr::Path(path) => {
+            let mut value = scope.get(&path[0]);
+            for key in &path[1..] {
+                value = match value {
+                    Some(Value::Table(table)) => table.get(key),
+                    Some(Value::Array(items)) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                    _ => None,
+                };
+            }
+            Ok(value.map(Cow::Borrowed))
+        }
+        Expr::Literal(value) => Ok(Some(Cow::Owned(value.clone()))),
+        Expr::Not(expr) => Ok(Some(Cow::Owned(Value::Boolean(!is_truthy(eval(expr, scope)?.as_deref()))))),
+        Expr::And(left, right) => {
+            let result = is_truthy(eval(left, scope)?.as_deref()) && is_truthy(eval(right, scope)?.as_deref());
+            Ok(Some(Cow::Owned(Value::Boolean(result))))
+        }
+        Expr::Or(left, right) => {
+            let result = is_truthy(eval(left, scope)?.as_deref()) || is_truthy(eval(right, scope)?.as_deref());
+            Ok(Some(Cow::Owned(Value::Boolean(result))))
+        }
+        Expr::Compare(left, op, right) => {
+            let ordering = compare(eval(left, scope)?.as_deref(), eval(right, scope)?.as_deref());
+            let result = match op {
+                CompareOp::Eq => ordering == Some(Ordering::Equal),
+                CompareOp::Ne => ordering != Some(Ordering::Equal),
+                CompareOp::Lt => ordering == Some(Ordering::Less),
+                CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                CompareOp::Gt => ordering == Some(Ordering::Greater),
+                CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            };
+            Ok(Some(Cow::Owned(Value::Boolean(result))))
+        }
+        Expr::Filter { expr: inner, name, args } => {
+            let value = eval(inner, scope)?.map(Cow::into_owned);
+            if value.is_none() && name != "default" {
+                return Err(format!("undefined variable \"{}\"", describe(inner)));
+            }
+            let mut arg_values = Vec::new();
+            for arg in args {
+                arg_values.push(eval(arg, scope)?.map(Cow::into_owned).ok_or(format!("undefined variable \"{}\"", describe(arg)))?);
+            }
+            Ok(Some(Cow::Owned(filters::apply(name, value, &arg_values)?)))
+        }
+    };
+}
+
+/// Empty strings, lists and tables, zero, `false` and undefined are false.
+fn is_truthy(value: Option<&Value>) -> bool {
+    return match value {
+        None => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Integer(i)) => *i != 0,
+        Some(Value::Float(n)) => *n != 0.0,
+        Some(Value::Boolean(b)) => *b,
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Table(table)) => !table.is_empty(),
+    };
+}
+
+/// Numbers compare by value, everything else only to the same type.
+fn compare(left: Option<&Value>, right: Option<&Value>) -> Option<Ordering> {
+    return match (left?, right?) {
+        (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+        (Value::Integer(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+        (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    };
+}
+
+/// The expression as written, for error messages.
+fn describe(expr: &Expr) -> String {
+    return match expr {
+        Expr::Path(path) => path.join("."),
+        Expr::Filter { expr, name, .. } => format!("{} | {name}", describe(expr)),
+        _ => "expression".to_string(),
+    };
+}
@@ -0,0 +1,96 @@
+use crate::generators::renderer::{Renderer, Image};
+
+/// Renders markdown to plain text, dropping all markup. The build uses it
+/// for a page's `text` and `summary_text`, for search indexes and summaries.
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn heading(&mut self, _level: usize, content: &str) -> String {
+        return format!("{content}\n\n");
+    }
+
+    fn paragraph(&mut self, content: &str) -> String {
+        return format!("{content}\n\n");
+    }
+
+    fn ordered_list(&mut self, items: &[String]) -> String {
+        return format!("{}\n\n", items.join("\n"));
+    }
+
+    fn definition_list(&mut self, entries: &[(String, Vec<String>)]) -> String {
+        let mut str_vec: Vec<String> = vec![];
+        for (term, definitions) in entries {
+            str_vec.push(term.to_string());
+            str_vec.extend(definitions.iter().cloned());
+        }
+        return format!("{}\n\n", str_vec.join("\n"));
+    }
+
+    fn code_block(&mut self, _language: &str, code: &str) -> String {
+        return format!("{code}\n\n");
+    }
+
+    fn figure(&mut self, image: &Image, caption: Option<&str>) -> String {
+        return format!("{}\n\n", caption.unwrap_or(&image.alt));
+    }
+
+    fn inline_block(&mut self, content: &str) -> String {
+        return format!("{content}\n\n");
+    }
+
+    fn text(&mut self, text: &str) -> String {
+        return text.to_string();
+    }
+
+    fn abbreviation(&mut self, abbr: &str, _title: &str) -> String {
+        return abbr.to_string();
+    }
+
+    fn link(&mut self, _href: &str, content: &str) -> String {
+        return content.to_string();
+    }
+
+    fn image(&mut self, image: &Image) -> String {
+        return image.alt.to_string();
+    }
+
+    fn code_span(&mut self, code: &str) -> String {
+        return code.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::Generator;
+    use crate::parsers::markdown::{Lexer, Extensions};
+
+    use super::TextRenderer;
+
+    fn get_text(input: &str) -> String {
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer");
+        let mut generator = Generator::with_renderer(lexer, Extensions::default(), TextRenderer);
+        return generator.generate().expect("ERROR: Couldn't get text");
+    }
+
+    #[test]
+    fn get_text_strips_markup() {
+        let input = "# Heading\n\
+                     \n\
+                     A [link](https://example.com) and ![alt](a.png) with `code`\n\
+                     \n\
+                     1. A\n\
+                     2. B";
+        let expected = "Heading\n\nA link and alt with code\n\nA\nB\n\n";
+
+        assert_eq!(get_text(input), expected);
+    }
+
+    #[test]
+    fn get_text_keeps_abbreviation_text() {
+        let input = "*[HTML]: Hyper Text Markup Language\n\
+                     HTML rocks";
+        let expected = "HTML rocks\n\n";
+
+        assert_eq!(get_text(input), expected);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod commands;
+pub mod config;
 pub mod generators;
 pub mod parsers;
 pub mod server;
+pub mod site;
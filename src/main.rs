@@ -37,6 +37,12 @@ Available Commands:
   version     Print the version number of Hugo
   build       Build your site
 
-Flags:
-None yet.
+Flags (build, run, new):
+  --config    Use this config file instead of afskylia.toml
+
+Flags (build, run):
+  --drafts    Include content marked "published: false"
+  --future    Include content with a publishDate in the future
+  --expired   Include content with an expiryDate in the past
+  --warn-only Report schema.toml violations without failing the build
   "#;
@@ -0,0 +1,68 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Abbreviation {
+    pub abbr: String,
+    pub title: String,
+}
+
+impl Abbreviation {
+    /// Parses a definition line like `*[HTML]: Hyper Text Markup Language`.
+    pub fn parse_line(line: &str) -> Option<Abbreviation> {
+        let rest = line.strip_prefix("*[")?;
+        let (abbr, rest) = rest.split_once("]:")?;
+        let abbr = abbr.trim();
+        let title = rest.trim();
+        if abbr.is_empty() || title.is_empty() {
+            return None;
+        }
+
+        return Some(Abbreviation { abbr: abbr.to_string(), title: title.to_string() });
+    }
+}
+
+/// Collects every abbreviation definition in the document, longest first so
+/// that `HTML5` wins over `HTML` when both are defined.
+pub fn find_abbreviations(input: &str) -> Vec<Abbreviation> {
+    let mut abbreviations = input.lines()
+        .filter_map(Abbreviation::parse_line)
+        .collect::<Vec<Abbreviation>>();
+    abbreviations.sort_by_key(|a| std::cmp::Reverse(a.abbr.chars().count()));
+    return abbreviations;
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_none, assert_some_eq};
+
+    use super::{Abbreviation, find_abbreviations};
+
+    #[test]
+    fn parse_line_returns_abbreviation() {
+        let result = Abbreviation::parse_line("*[HTML]: Hyper Text Markup Language");
+
+        let expected = Abbreviation { abbr: "HTML".to_string(), title: "Hyper Text Markup Language".to_string() };
+        assert_some_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_line_without_title_is_none() {
+        assert_none!(Abbreviation::parse_line("*[HTML]:"));
+    }
+
+    #[test]
+    fn parse_line_regular_text_is_none() {
+        assert_none!(Abbreviation::parse_line("*emphasis* [link]"));
+    }
+
+    #[test]
+    fn find_abbreviations_longest_first() {
+        let input = "Text\n\
+                     *[HTML]: Hyper Text Markup Language\n\
+                     *[HTML5]: Hyper Text Markup Language 5";
+
+        let result = find_abbreviations(input);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].abbr, "HTML5");
+        assert_eq!(result[1].abbr, "HTML");
+    }
+}
@@ -0,0 +1,88 @@
+/// Attributes in the `{#id .class key=value}` syntax that can follow an image.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attributes {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub pairs: Vec<(String, String)>,
+}
+
+impl Attributes {
+    /// Parses the text between the braces, e.g. `width=300 .wide loading="lazy"`.
+    pub fn parse(input: &str) -> Option<Attributes> {
+        let mut attributes = Attributes::default();
+        let mut chars = input.chars().peekable();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let Some(first) = chars.next() else {
+                break;
+            };
+
+            let mut word = String::default();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+                word.push(c);
+            }
+
+            match first {
+                '.' if !word.is_empty() => attributes.classes.push(word),
+                '#' if !word.is_empty() => attributes.id = Some(word),
+                c if c.is_alphanumeric() => {
+                    let key = format!("{c}{word}");
+                    if !key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+                        return None;
+                    }
+                    chars.next_if_eq(&'=')?;
+
+                    let mut value = String::default();
+                    if chars.next_if_eq(&'"').is_some() {
+                        while let Some(c) = chars.next_if(|c| *c != '"') {
+                            value.push(c);
+                        }
+                        chars.next_if_eq(&'"')?;
+                    } else {
+                        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                            value.push(c);
+                        }
+                    }
+                    attributes.pairs.push((key, value));
+                },
+                _ => return None,
+            }
+        }
+
+        return Some(attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_none, assert_some};
+
+    use super::Attributes;
+
+    #[test]
+    fn parse_attributes() {
+        let result = assert_some!(Attributes::parse("#logo .wide .rounded width=300 loading=\"lazy\""));
+
+        assert_eq!(result.id, Some("logo".to_string()));
+        assert_eq!(result.classes, vec!["wide".to_string(), "rounded".to_string()]);
+        assert_eq!(result.pairs, vec![("width".to_string(), "300".to_string()), ("loading".to_string(), "lazy".to_string())]);
+    }
+
+    #[test]
+    fn parse_empty_attributes() {
+        let result = assert_some!(Attributes::parse(""));
+
+        assert_eq!(result, Attributes::default());
+    }
+
+    #[test]
+    fn parse_key_without_value_is_none() {
+        assert_none!(Attributes::parse("width"));
+    }
+
+    #[test]
+    fn parse_unclosed_quote_is_none() {
+        assert_none!(Attributes::parse("alt=\"unclosed"));
+    }
+}
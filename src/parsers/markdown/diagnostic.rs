@@ -0,0 +1,58 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    UnclosedLink,
+    UnclosedFence,
+    UnknownConstruct(String),
+}
+
+/// A problem found while rendering markdown. The document still renders,
+/// the offending text is kept as plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, line: usize, column: usize) -> Self {
+        return Diagnostic { kind, line, column };
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticKind::UnclosedLink => write!(f, "unclosed link"),
+            DiagnosticKind::UnclosedFence => write!(f, "unclosed code fence"),
+            DiagnosticKind::UnknownConstruct(construct) => write!(f, "unknown construct: {construct}"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, DiagnosticKind};
+
+    #[test]
+    fn display_diagnostic() {
+        let diagnostic = Diagnostic::new(DiagnosticKind::UnclosedLink, 3, 14);
+
+        assert_eq!(diagnostic.to_string(), "3:14: unclosed link");
+    }
+
+    #[test]
+    fn display_unknown_construct() {
+        let diagnostic = Diagnostic::new(DiagnosticKind::UnknownConstruct("blockquote".to_string()), 1, 1);
+
+        assert_eq!(diagnostic.to_string(), "1:1: unknown construct: blockquote");
+    }
+}
@@ -0,0 +1,153 @@
+static EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("alarm_clock", "⏰"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("bell", "🔔"),
+    ("blush", "😊"),
+    ("book", "📖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("cat", "🐱"),
+    ("chart_with_upwards_trend", "📈"),
+    ("check", "✔️"),
+    ("clap", "👏"),
+    ("cloud", "☁️"),
+    ("coffee", "☕"),
+    ("computer", "💻"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cry", "😢"),
+    ("crab", "🦀"),
+    ("dog", "🐶"),
+    ("email", "📧"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("flag_se", "🇸🇪"),
+    ("gear", "⚙️"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("hourglass", "⌛"),
+    ("house", "🏠"),
+    ("hugs", "🤗"),
+    ("information_source", "ℹ️"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("moon", "🌙"),
+    ("muscle", "💪"),
+    ("no_entry", "⛔"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("party_popper", "🎉"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("point_right", "👉"),
+    ("pray", "🙏"),
+    ("question", "❓"),
+    ("rainbow", "🌈"),
+    ("recycle", "♻️"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("sad", "😞"),
+    ("scream", "😱"),
+    ("seedling", "🌱"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("snowflake", "❄️"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("sunny", "☀️"),
+    ("sunglasses", "😎"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("tree", "🌳"),
+    ("trophy", "🏆"),
+    ("umbrella", "☔"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+pub fn get_emoji(shortcode: &str) -> Option<&'static str> {
+    return EMOJI.iter()
+        .find(|(name, _)| *name == shortcode)
+        .map(|(_, emoji)| *emoji);
+}
+
+/// Replaces every known `:shortcode:` in the text. Unknown shortcodes, and
+/// colons that aren't part of one like in `10:30`, are left as is.
+pub fn replace_shortcodes(text: &str) -> String {
+    let mut res = String::default();
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        res.push_str(&rest[..start]);
+        let after_colon = &rest[start+1..];
+
+        let name_len = after_colon
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-'))
+            .unwrap_or(after_colon.len());
+        let is_closed = after_colon[name_len..].starts_with(':');
+
+        match get_emoji(&after_colon[..name_len]) {
+            Some(emoji) if is_closed => {
+                res.push_str(emoji);
+                rest = &after_colon[name_len+1..];
+            },
+            _ => {
+                res.push(':');
+                rest = after_colon;
+            }
+        }
+    }
+    res.push_str(rest);
+    return res;
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_none, assert_some_eq};
+
+    use super::{get_emoji, replace_shortcodes};
+
+    #[test]
+    fn get_emoji_known_shortcode() {
+        assert_some_eq!(get_emoji("rocket"), "🚀");
+    }
+
+    #[test]
+    fn get_emoji_unknown_shortcode() {
+        assert_none!(get_emoji("not_an_emoji"));
+    }
+
+    #[test]
+    fn replace_shortcodes_in_text() {
+        assert_eq!(replace_shortcodes("Launch :rocket::tada: now"), "Launch 🚀🎉 now");
+    }
+
+    #[test]
+    fn replace_shortcodes_keeps_unknown_and_times() {
+        assert_eq!(replace_shortcodes("At 10:30:00 :nope: :rocket"), "At 10:30:00 :nope: :rocket");
+    }
+}
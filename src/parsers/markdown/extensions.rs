@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extensions {
+    pub definition_lists: bool,
+    pub abbreviations: bool,
+    pub figures: bool,
+    pub emoji: bool,
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        return Extensions {
+            definition_lists: true,
+            abbreviations: true,
+            figures: false,
+            emoji: true,
+        };
+    }
+}
@@ -7,14 +7,12 @@ pub struct Lexer {
     read_position: usize,
     ch: Option<char>,
     prev: Option<char>,
+    line: usize,
+    column: usize,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Result<Lexer, String> {
-        if input.is_empty() {
-            return Err("Input is required".to_string());
-        }
-
         return Ok(Self::new_lexer_from_input(input));
     }
 
@@ -25,6 +23,8 @@ impl Lexer {
             read_position: 0,
             ch: None,
             prev: None,
+            line: 1,
+            column: 0,
         };
 
         return lexer;
@@ -41,9 +41,10 @@ impl Lexer {
             Some('(') => Token::new(TokenType::RParen, String::from('(')),
             Some(')') => Token::new(TokenType::LParen, String::from(')')),
             Some('!') => Token::new(TokenType::Bang, String::default()),
+            Some('`') => Token::new(TokenType::Backtick, String::from('`')),
             Some(t) => {
                 let prev = self.input.chars().nth(self.read_position+n-1);
-                if t.is_digit(10) && (prev.is_none() || prev.unwrap() == '\n') {
+                if t.is_ascii_digit() && (prev.is_none() || prev.unwrap() == '\n') {
                     Token::new(TokenType::OrderedItem, String::from(""))
                 } else {
                     Token::new(TokenType::Letter, String::from(t))
@@ -55,13 +56,45 @@ impl Lexer {
         return tok;
     }
 
+    pub fn peek_line(&self, n: usize) -> Option<String> {
+        let rest = self.input.chars().skip(self.read_position).collect::<String>();
+        return rest.split('\n').nth(n).map(|line| line.to_string());
+    }
+
+    pub fn skip_chars(&mut self, n: usize) {
+        for _ in 0..n {
+            self.read_char();
+        }
+    }
+
+    pub fn get_input(&self) -> &str {
+        return &self.input;
+    }
+
     pub fn peek_next_token(&mut self) -> Token {
         return self.peek_nth_token(0);
     }
 
+    /// Reads the rest of the current line, including the line break, and
+    /// returns it without the line break. Returns `None` at the end of input.
+    pub fn read_line(&mut self) -> Option<String> {
+        if self.read_position >= self.input.chars().count() {
+            return None;
+        }
+
+        let line = self.peek_line(0).unwrap_or_default();
+        self.skip_chars(line.chars().count() + 1);
+        return Some(line);
+    }
+
     pub fn next_token(&mut self) -> Token {
         self.read_char();
 
+        let (line, column) = (self.line, self.column);
+        return self.read_token().with_position(line, column);
+    }
+
+    fn read_token(&mut self) -> Token {
         let tok = match self.ch {
             Some('#') => {
                 if self.prev.is_none() || self.prev.unwrap() == '\n' || self.prev.unwrap() == '#' {
@@ -74,12 +107,13 @@ impl Lexer {
             },
             Some('\n') => Token::new(TokenType::LineBreak, String::from("")),
             Some('!') => Token::new(TokenType::Bang, String::from('!')),
+            Some('`') => Token::new(TokenType::Backtick, String::from('`')),
             Some('[') => Token::new(TokenType::RBracket, String::from('[')),
             Some(']') => Token::new(TokenType::LBracket, String::from(']')),
             Some('(') => Token::new(TokenType::RParen, String::from('(')),
             Some(')') => Token::new(TokenType::LParen, String::from(')')),
             Some(t) => {
-                if t.is_digit(10) && (self.prev.is_none() || self.prev.unwrap() == '\n') {
+                if t.is_ascii_digit() && (self.prev.is_none() || self.prev.unwrap() == '\n') {
                     //TODO: Support more than 1 digit numbers
                     self.read_char(); //to skip the dot. Which means we can only do 1-9 
                     self.read_char(); //skip the whitespace
@@ -98,6 +132,13 @@ impl Lexer {
         self.prev = self.ch;
         self.ch = self.input.chars().nth(self.read_position);
 
+        if self.prev == Some('\n') {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         self.position = self.read_position;
         self.read_position += 1;
     }
@@ -108,7 +149,7 @@ mod tests {
     use crate::parsers::markdown::TokenType;
 
     use super::Lexer;
-    use claim::{assert_ok, assert_err, assert_matches};
+    use claim::{assert_ok, assert_matches};
 
     #[test]
     fn image_in_paragraph() {
@@ -212,11 +253,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn peek_line() {
+        let input = "Term\n\
+                     : Definition";
+
+        let mut lexer = Lexer::new(input).unwrap();
+        lexer.next_token();
+
+        assert_eq!(lexer.peek_line(0), Some("erm".to_string()));
+        assert_eq!(lexer.peek_line(1), Some(": Definition".to_string()));
+        assert_eq!(lexer.peek_line(2), None);
+    }
+
     #[test]
     fn peek_nth_token() {
         let input = "ABC";
 
-        let lexer = Lexer::new(&input).expect("ERROR: Couldn't create new lexer");
+        let lexer = Lexer::new(input).expect("ERROR: Couldn't create new lexer");
 
         for i in 1..3 {
             println!("{}", i);
@@ -231,9 +285,9 @@ mod tests {
         let input = "L\n\
                      1. A";
 
-        let expected = vec![TokenType::Letter, TokenType::LineBreak,
+        let expected = [TokenType::Letter, TokenType::LineBreak,
         TokenType::OrderedItem, TokenType::Letter, TokenType::EOF];
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for (i, e) in expected.iter().enumerate() {
             let tok = lexer.next_token();
@@ -250,7 +304,7 @@ mod tests {
                      \n\
                      1. A";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         lexer.next_token();
         lexer.next_token();
@@ -263,7 +317,7 @@ mod tests {
         let input = "\n\
                      1. A";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         lexer.next_token();
         let peek_token = lexer.peek_next_token();
@@ -282,7 +336,7 @@ mod tests {
         TokenType::OrderedItem, TokenType::Letter, TokenType::LineBreak,
         TokenType::OrderedItem, TokenType::Letter, TokenType::EOF];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -299,7 +353,7 @@ mod tests {
         TokenType::LineBreak, TokenType::OrderedItem, 
         TokenType::Letter, TokenType::EOF];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -312,7 +366,7 @@ mod tests {
     fn peek_next_token() {
         let input = "L\n";
         
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         let token = lexer.next_token();
         let peek_token = lexer.peek_next_token();
@@ -329,7 +383,7 @@ mod tests {
         TokenType::Letter, TokenType::Letter, TokenType::Letter,
         TokenType::EOF];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -348,7 +402,7 @@ mod tests {
         TokenType::LineBreak, TokenType::LineBreak, TokenType::Letter,
         TokenType::Letter];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -366,7 +420,7 @@ mod tests {
         TokenType::LineBreak, TokenType::OrderedItem, TokenType::Letter,
         TokenType::LineBreak, TokenType::EOF];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -383,7 +437,7 @@ mod tests {
         TokenType::LineBreak, TokenType::OrderedItem, TokenType::Letter,
         TokenType::EOF];
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -401,7 +455,7 @@ mod tests {
         let expected = vec![TokenType::LineBreak, TokenType::OrderedItem,
         TokenType::Letter, TokenType::LineBreak, TokenType::OrderedItem,
         TokenType::Letter, TokenType::LineBreak, TokenType::EOF];
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -420,7 +474,7 @@ mod tests {
         TokenType::Letter, TokenType::Letter, TokenType::LineBreak,
         TokenType::Letter, TokenType::Letter, TokenType::Letter,
         TokenType::Letter, TokenType::LineBreak, TokenType::EOF];
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             println!("expected: {}", e);
@@ -434,7 +488,7 @@ mod tests {
         let input = "# He";
         let expected = vec![TokenType::Heading, 
         TokenType::Letter, TokenType::Letter, TokenType::EOF];
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         for e in expected {
             let tok = lexer.next_token();
@@ -446,7 +500,7 @@ mod tests {
     fn next_token_heading_twice() {
         let input = "##";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
         let mut tok = lexer.next_token();
         assert_matches!(tok.token_type, TokenType::Heading);
         tok = lexer.next_token();
@@ -457,7 +511,7 @@ mod tests {
     fn next_token_letter_space() {
         let input = " ";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
         let tok = lexer.next_token();
 
         assert_matches!(tok.token_type, TokenType::Letter);
@@ -469,7 +523,7 @@ mod tests {
         let input = "\n\
                      a";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         assert_matches!(lexer.next_token().token_type, TokenType::LineBreak);
         assert_matches!(lexer.next_token().token_type, TokenType::Letter);
@@ -480,7 +534,7 @@ mod tests {
         let input = "\n\
                      ";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
 
         assert_matches!(lexer.next_token().token_type, TokenType::LineBreak);
     }
@@ -489,7 +543,7 @@ mod tests {
     fn next_token_letter_a() {
         let input = "a";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
         let tok = lexer.next_token();
 
         assert_matches!(tok.token_type, TokenType::Letter);
@@ -500,7 +554,7 @@ mod tests {
     fn next_token_heading() {
         let input = "#";
 
-        let mut lexer = Lexer::new(&input).unwrap();
+        let mut lexer = Lexer::new(input).unwrap();
         let tok = lexer.next_token();
 
         assert_matches!(tok.token_type, TokenType::Heading);
@@ -513,8 +567,55 @@ mod tests {
     }
 
     #[test]
-    fn lexer_new_empty_input_not_ok() {
+    fn lexer_new_empty_input_ok() {
         let input = "".to_string();
-        assert_err!(Lexer::new(&input));
+        let mut lexer = assert_ok!(Lexer::new(&input));
+
+        assert_matches!(lexer.next_token().token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn next_token_tracks_line_and_column() {
+        let input = "# He\n\
+                     ab";
+
+        let mut lexer = Lexer::new(input).unwrap();
+        let heading = lexer.next_token();
+        assert_eq!((heading.line, heading.column), (1, 1));
+
+        let letter = lexer.next_token();
+        assert_eq!((letter.line, letter.column), (1, 3));
+
+        lexer.next_token();
+        let line_break = lexer.next_token();
+        assert_eq!((line_break.line, line_break.column), (1, 5));
+
+        lexer.next_token();
+        let letter = lexer.next_token();
+        assert_eq!((letter.line, letter.column), (2, 2));
+    }
+
+    #[test]
+    fn read_line_consumes_line() {
+        let input = "```rust\n\
+                     let a = 1;";
+
+        let mut lexer = Lexer::new(input).unwrap();
+        lexer.next_token();
+
+        assert_eq!(lexer.read_line(), Some("``rust".to_string()));
+        assert_eq!(lexer.read_line(), Some("let a = 1;".to_string()));
+        assert_eq!(lexer.read_line(), None);
+    }
+
+    #[test]
+    fn next_token_backtick() {
+        let input = "`a`";
+
+        let mut lexer = Lexer::new(input).unwrap();
+
+        assert_matches!(lexer.next_token().token_type, TokenType::Backtick);
+        assert_matches!(lexer.next_token().token_type, TokenType::Letter);
+        assert_matches!(lexer.next_token().token_type, TokenType::Backtick);
     }
 }
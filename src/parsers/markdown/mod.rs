@@ -1,5 +1,15 @@
 mod token;
 mod lexer;
+mod extensions;
+mod abbreviation;
+mod attributes;
+mod diagnostic;
+mod emoji;
 
 pub use token::{Token, TokenType};
 pub use lexer::Lexer;
+pub use extensions::Extensions;
+pub use abbreviation::{Abbreviation, find_abbreviations};
+pub use attributes::Attributes;
+pub use diagnostic::{Diagnostic, DiagnosticKind};
+pub use emoji::replace_shortcodes;
@@ -4,6 +4,8 @@ use core::fmt;
 pub struct Token {
     pub token_type: TokenType,
     pub literal: String,
+    pub line: usize,
+    pub column: usize,
 }
 
 impl Token {
@@ -11,8 +13,14 @@ impl Token {
         return Token { 
             token_type,
             literal,
+            line: 0,
+            column: 0,
         };
     }
+
+    pub fn with_position(self, line: usize, column: usize) -> Token {
+        return Token { line, column, ..self };
+    }
 }
 
 impl fmt::Display for Token {
@@ -36,6 +44,7 @@ pub enum TokenType {
     RBracket,
     LBracket,
     Bang,
+    Backtick,
 }
 
 impl fmt::Debug for TokenType {
@@ -53,6 +62,7 @@ impl fmt::Debug for TokenType {
             TokenType::RBracket => write!(f, "TokenType::RBracket"),
             TokenType::LBracket => write!(f, "TokenType::LBracket"),
             TokenType::Bang => write!(f, "TokenType::Bang"),
+            TokenType::Backtick => write!(f, "TokenType::Backtick"),
         }
     }
 }
@@ -72,6 +82,7 @@ impl fmt::Display for TokenType {
             TokenType::RBracket => write!(f, "TokenType::RBracket"),
             TokenType::LBracket => write!(f, "TokenType::LBracket"),
             TokenType::Bang => write!(f, "TokenType::Bang"),
+            TokenType::Backtick => write!(f, "TokenType::Backtick"),
         }
     }
 }
@@ -91,6 +102,13 @@ mod tests {
         assert_eq!(token.literal, String::from("A"));
     }
 
+    #[test]
+    fn create_token_with_position() {
+        let token = Token::new(TokenType::Letter, "A".to_string()).with_position(2, 5);
+        assert_eq!(token.line, 2);
+        assert_eq!(token.column, 5);
+    }
+
     #[test]
     fn create_heading_token() {
         let token = Token::new(TokenType::Heading, "".to_string());
@@ -1,155 +1,356 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{cmp::Ordering, fmt::Display, time::SystemTime};
 
-#[derive(Debug, PartialEq)]
+static MONTH_NAMES: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July",
+    "August", "September", "October", "November", "December"];
+static WEEKDAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// A point in time as written in front matter. The date and time fields are
+/// the wall clock in `offset`, `timestamp` is the same instant in UTC.
+/// Comparisons are by instant, so the same time written in two offsets is
+/// equal.
+#[derive(Debug, Clone)]
 pub struct AfDateTime {
-    timestamp: usize,
+    timestamp: i64,
     day: usize,
     month: usize,
     year: usize,
     hour: usize,
     minute: usize,
-    second: usize
+    second: usize,
+    nanosecond: u32,
+    /// Minutes east of UTC
+    offset: i32,
+    /// Whether the offset was written out, so it's shown again by `Display`
+    has_offset: bool,
 }
 
 impl AfDateTime {
     pub fn new(timestamp: usize) -> Self {
-        let map = Self::parse_timestamp(timestamp);
+        return AfDateTime::from_timestamp(timestamp as i64);
+    }
 
-        let second = *map.get("second").expect("ERROR: Couldn't get second");
-        let minute = *map.get("minute").expect("ERROR: Couldn't get minute");
-        let hour = *map.get("hour").expect("ERROR: Couldn't get hour");
-        let day = *map.get("day").expect("ERROR: Couldn't get day");
-        let month = *map.get("month").expect("ERROR: Couldn't get month");
-        let year = *map.get("year").expect("ERROR: Couldn't get year");
+    /// The UTC datetime for seconds since the unix epoch, negative before 1970.
+    fn from_timestamp(timestamp: i64) -> Self {
+        let seconds_of_day = timestamp.rem_euclid(86400) as usize;
+        let second = seconds_of_day % 60;
+        let minute = (seconds_of_day / 60) % 60;
+        let hour = seconds_of_day / 60 / 60;
+        let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
 
-        return AfDateTime { timestamp, second, minute, hour, day, month, year };
+        return AfDateTime { timestamp, second, minute, hour, day, month, year, nanosecond: 0, offset: 0, has_offset: false };
     }
 
-    fn parse_timestamp(timestamp: usize) -> HashMap<String, usize> {
-        let mut map = HashMap::default();
-        let days_since_1970 = timestamp / 86400;
+    pub fn now() -> Self {
+        return AfDateTime::from(SystemTime::now());
+    }
 
-        let second = timestamp % 60;
-        let minute = (timestamp / 60) % 60;
-        let hour = (timestamp / 60 / 60) % 24;
-        let day = timestamp / 60 / 60 / 24;
+    /// Seconds since the unix epoch, in UTC.
+    pub fn get_timestamp(&self) -> i64 {
+        return self.timestamp;
+    }
 
-        let years = (day as f64 / 365.25) as usize;
-        let year = 1970 + years;
+    pub fn get_nanosecond(&self) -> u32 {
+        return self.nanosecond;
+    }
+
+    /// The offset the date was written in, in minutes east of UTC.
+    pub fn get_offset(&self) -> i32 {
+        return self.offset;
+    }
+
+    /// The same instant with the wall clock in UTC.
+    pub fn to_utc(&self) -> AfDateTime {
+        let mut utc = AfDateTime::from_timestamp(self.timestamp);
+        utc.nanosecond = self.nanosecond;
+        utc.has_offset = self.has_offset;
+        return utc;
+    }
 
-        let leap_year_count = days_since_1970 / 1461;
+    /// Parses an offset like `Z`, `UTC`, `+02:00` or `-0530` into minutes
+    /// east of UTC.
+    pub fn parse_offset(input: &str) -> Result<i32, String> {
+        let input = input.trim();
+        if matches!(input, "Z" | "z" | "UTC") {
+            return Ok(0);
+        }
 
-        let mut days_this_year = (days_since_1970 - (leap_year_count * 1461)) % 365;
+        let sign = match input.chars().next() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _ => return Err(format!("Expected an offset like +02:00, found {input:?}")),
+        };
+        let digits = input[1..].replace(':', "");
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Expected an offset like +02:00, found {input:?}"));
+        }
 
-        let mut leap_year = false;
-        if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) {
-            leap_year = true;
-            days_this_year += 1;
+        let hours = digits[..2].parse::<i32>().expect("ERROR: Checked digits");
+        let minutes = digits[2..].parse::<i32>().expect("ERROR: Checked digits");
+        if hours > 23 || minutes > 59 {
+            return Err(format!("Offset out of range: {input:?}"));
         }
+        return Ok(sign * (hours * 60 + minutes));
+    }
 
-        let days_in_month: [usize; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-        let mut month = 0;
-        while days_this_year > days_in_month[month] {
-            let mut days_this_month = days_in_month[month];
-            if month == 1 && leap_year {
-                days_this_month += 1;
+    pub fn get_year(&self) -> usize {
+        return self.year;
+    }
+
+    pub fn get_month(&self) -> usize {
+        return self.month;
+    }
+
+    pub fn get_day(&self) -> usize {
+        return self.day;
+    }
+
+    pub fn get_hour(&self) -> usize {
+        return self.hour;
+    }
+
+    pub fn get_minute(&self) -> usize {
+        return self.minute;
+    }
+
+    pub fn get_second(&self) -> usize {
+        return self.second;
+    }
+
+    /// Days since Monday of the local date, so Monday is 0 and Sunday is 6.
+    pub fn get_weekday(&self) -> usize {
+        let days = days_from_civil(self.year, self.month, self.day);
+        // 1970-01-01 was a Thursday
+        return (days + 3).rem_euclid(7) as usize;
+    }
+
+    /// Formats the date with strftime-like specifiers:
+    ///
+    /// `%Y` year, `%y` two digit year, `%m` month, `%d` day, `%e` space padded
+    /// day, `%H` hour, `%I` 12 hour clock, `%p` AM/PM, `%M` minute, `%S`
+    /// second, `%B`/`%b` month name, `%A`/`%a` weekday name, `%z` offset as
+    /// `+0200`, `%:z` offset as `+02:00` and `%%`. A `-` after the `%` drops
+    /// the zero padding, so `%-d %B %Y` gives "8 April 2023".
+    pub fn format(&self, pattern: &str) -> String {
+        let mut result = String::new();
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push(c);
+                continue;
+            }
+
+            let no_padding = chars.next_if_eq(&'-').is_some();
+            let colon = chars.next_if_eq(&':').is_some();
+            let number = |value: usize, width: usize| {
+                return match no_padding {
+                    true => value.to_string(),
+                    false => format!("{value:0width$}"),
+                };
+            };
+
+            match chars.next() {
+                Some('Y') => result.push_str(&number(self.year, 4)),
+                Some('y') => result.push_str(&number(self.year % 100, 2)),
+                Some('m') => result.push_str(&number(self.month, 2)),
+                Some('d') => result.push_str(&number(self.day, 2)),
+                Some('e') => result.push_str(&format!("{:>2}", self.day)),
+                Some('H') => result.push_str(&number(self.hour, 2)),
+                Some('I') => result.push_str(&number((self.hour + 11) % 12 + 1, 2)),
+                Some('p') => result.push_str(if self.hour < 12 { "AM" } else { "PM" }),
+                Some('M') => result.push_str(&number(self.minute, 2)),
+                Some('S') => result.push_str(&number(self.second, 2)),
+                Some('B') => result.push_str(MONTH_NAMES[self.month - 1]),
+                Some('b') => result.push_str(&MONTH_NAMES[self.month - 1][..3]),
+                Some('A') => result.push_str(WEEKDAY_NAMES[self.get_weekday()]),
+                Some('a') => result.push_str(&WEEKDAY_NAMES[self.get_weekday()][..3]),
+                Some('z') => {
+                    let sign = if self.offset < 0 { '-' } else { '+' };
+                    let separator = if colon { ":" } else { "" };
+                    result.push_str(&format!("{sign}{:02}{separator}{:02}", self.offset.abs() / 60, self.offset.abs() % 60));
+                }
+                Some('%') => result.push('%'),
+                // Unknown specifiers are kept as written
+                Some(other) => {
+                    result.push('%');
+                    result.push(other);
+                }
+                None => result.push('%'),
             }
-            days_this_year -= days_this_month;
-            month += 1;
         }
-        let day = days_this_year+1;
-        map.insert(String::from("hour"), hour);
-        map.insert(String::from("minute"), minute);
-        map.insert(String::from("second"), second);
-        map.insert(String::from("year"), year);
-        map.insert(String::from("month"), 1+month);
-        map.insert(String::from("day"), day);
-        return map;
+
+        return result;
+    }
+
+    /// The date as used in RSS feeds, e.g. `Sat, 08 Apr 2023 10:17:00 +0200`.
+    pub fn to_rfc822(&self) -> String {
+        return self.format("%a, %d %b %Y %H:%M:%S %z");
+    }
+
+    /// The date with its offset always written out, as used in sitemaps and
+    /// Atom feeds, e.g. `2023-04-08T10:17:00+02:00`.
+    pub fn to_rfc3339(&self) -> String {
+        let mut with_offset = self.clone();
+        with_offset.has_offset = true;
+        return with_offset.to_string();
+    }
+}
+
+impl PartialEq for AfDateTime {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl Eq for AfDateTime {}
+
+impl PartialOrd for AfDateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for AfDateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return (self.timestamp, self.nanosecond).cmp(&(other.timestamp, other.nanosecond));
     }
 }
 
 impl Display for AfDateTime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", self.year, self.month,
-               self.day, self.hour, self.minute, self.second)
+               self.day, self.hour, self.minute, self.second)?;
+        if self.nanosecond != 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        if self.has_offset {
+            match self.offset {
+                0 => write!(f, "Z")?,
+                offset => {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    write!(f, "{sign}{:02}:{:02}", offset.abs() / 60, offset.abs() % 60)?;
+                }
+            }
+        }
+        return Ok(());
     }
 }
 
 #[derive(Debug)]
 struct AfDateTimeParser {
-    input: String,
+    input: Vec<char>,
     position: usize,
-    read_position: usize,
-    ch: Option<char>,
-    prev: Option<char>,
 }
 
 impl AfDateTimeParser {
-    fn new_from_string(input: String) -> Result<AfDateTimeParser, String> {
+    fn new_from_str(input: &str) -> Result<AfDateTimeParser, String> {
         if input.is_empty() {
             return Err("Input is required".to_string());
         }
 
-        return Ok(Self::new_lexer_from_input(&input));
+        return Ok(AfDateTimeParser { input: input.trim().chars().collect(), position: 0 });
     }
-    fn new_from_str(input: &str) -> Result<AfDateTimeParser, String> {
-        if input.is_empty() {
-            return Err("Input is required".to_string());
-        }
 
-        return Ok(Self::new_lexer_from_input(input));
+    fn peek(&self) -> Option<char> {
+        return self.input.get(self.position).copied();
     }
 
-    fn new_lexer_from_input(input: &str) -> AfDateTimeParser {
-        let lexer = AfDateTimeParser {
-            input: input.to_string(),
-            position: 0,
-            read_position: 0,
-            ch: None,
-            prev: None,
+    fn read_char(&mut self) -> Option<char> {
+        let ch = self.peek();
+        self.position += 1;
+        return ch;
+    }
+
+    fn read_if(&mut self, expected: &[char]) -> Option<char> {
+        return match self.peek() {
+            Some(c) if expected.contains(&c) => self.read_char(),
+            _ => None,
         };
+    }
 
-        return lexer;
+    fn expect_char(&mut self, expected: char) -> Result<(), String> {
+        return match self.read_char() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(format!("Expected {expected:?} in datetime {:?}", self.input.iter().collect::<String>())),
+        };
     }
 
-    fn read_char(&mut self) {
-        self.prev = self.ch;
-        self.ch = self.input.chars().nth(self.read_position);
+    /// Reads exactly `digits` digits.
+    fn get_number(&mut self, digits: usize) -> Result<usize, String> {
+        let mut result = 0;
+        for _ in 0..digits {
+            match self.read_char().and_then(|c| c.to_digit(10)) {
+                Some(digit) => result = result * 10 + digit as usize,
+                None => return Err(format!("Expected a number in datetime {:?}", self.input.iter().collect::<String>())),
+            }
+        }
+        return Ok(result);
+    }
+}
 
-        self.position = self.read_position;
-        self.read_position += 1;
+impl AfDateTime {
+    /// Parses an RFC 3339 datetime like `2023-04-08T10:17:00.5+02:00`. Dates
+    /// without an offset, and date only values like `2023-04-08`, are in UTC.
+    pub fn parse(input: &str) -> Result<AfDateTime, String> {
+        return AfDateTime::parse_with_offset(input, 0);
     }
 
-    fn get_next_number(&mut self) -> Result<usize, String> {
-        let mut result_string = String::default();
-        self.read_char();
+    /// Like `parse`, but dates without an offset are in `default_offset`
+    /// minutes east of UTC, e.g. the site's timezone.
+    pub fn parse_with_offset(input: &str, default_offset: i32) -> Result<AfDateTime, String> {
+        let mut parser = AfDateTimeParser::new_from_str(input)?;
+        let year = parser.get_number(4)?;
+        parser.expect_char('-')?;
+        let month = parser.get_number(2)?;
+        parser.expect_char('-')?;
+        let day = parser.get_number(2)?;
+
+        let (mut hour, mut minute, mut second, mut nanosecond) = (0, 0, 0, 0);
+        let mut offset = default_offset;
+        let mut has_offset = false;
+
+        if parser.read_if(&['T', 't', ' ']).is_some() {
+            hour = parser.get_number(2)?;
+            parser.expect_char(':')?;
+            minute = parser.get_number(2)?;
+            parser.expect_char(':')?;
+            second = parser.get_number(2)?;
+
+            if parser.read_if(&['.']).is_some() {
+                // Only nanosecond precision is kept
+                let mut digits = 0;
+                while let Some(digit) = parser.peek().and_then(|c| c.to_digit(10)) {
+                    parser.read_char();
+                    if digits < 9 {
+                        nanosecond = nanosecond * 10 + digit;
+                        digits += 1;
+                    }
+                }
+                if digits == 0 {
+                    return Err(format!("Expected fractional seconds in datetime {input:?}"));
+                }
+                nanosecond *= 10_u32.pow(9 - digits);
+            }
 
-        while let Some(i) = self.ch {
-            if !i.is_numeric() {
-                break;
+            if parser.peek().is_some() {
+                let rest = parser.input[parser.position..].iter().collect::<String>();
+                offset = AfDateTime::parse_offset(&rest)?;
+                has_offset = true;
+                parser.position = parser.input.len();
             }
-            result_string.push(i);
-            self.read_char();
         }
 
-        let res: usize = result_string.parse().expect("ERROR: Couldn't parse result_string to usize");
-        return Ok(res);
-    }
-}
-
-impl From<String> for AfDateTime {
-    fn from(value: String) -> Self {
-        let mut parser = AfDateTimeParser::new_from_string(value).expect("ERROR: Couldn't generate a new datetimeparser");
-        let year = parser.get_next_number().expect("ERROR: Couldn't get year");
-        let month = parser.get_next_number().expect("ERROR: Couldn't get month");
-        let day = parser.get_next_number().expect("ERROR: Couldn't get day");
-        let hour = parser.get_next_number().expect("ERROR: Couldn't get hour");
-        let minute = parser.get_next_number().expect("ERROR: Couldn't get minutes");
-        let second = parser.get_next_number().expect("ERROR: Couldn't get seconds");
+        if parser.peek().is_some() {
+            return Err(format!("Unexpected text after datetime: {input:?}"));
+        }
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+            return Err(format!("Datetime out of range: {input:?}"));
+        }
 
-        let timestamp = get_timestamp(year, month, day, hour, minute, second);
+        let timestamp = get_timestamp(year, month, day, hour, minute, second) - offset as i64 * 60;
 
-        return AfDateTime {
+        return Ok(AfDateTime {
             timestamp,
             year,
             month,
@@ -157,68 +358,74 @@ impl From<String> for AfDateTime {
             hour,
             minute,
             second,
-        };
+            nanosecond,
+            offset,
+            has_offset,
+        });
     }
 }
 
-impl From<&str> for AfDateTime {
-    fn from(value: &str) -> Self {
-        let mut parser = AfDateTimeParser::new_from_str(value).expect("ERROR: Couldn't generate a new datetimeparser");
-        let year = parser.get_next_number().expect("ERROR: Couldn't get year");
-        let month = parser.get_next_number().expect("ERROR: Couldn't get month");
-        let day = parser.get_next_number().expect("ERROR: Couldn't get day");
-        let hour = parser.get_next_number().expect("ERROR: Couldn't get hour");
-        let minute = parser.get_next_number().expect("ERROR: Couldn't get minutes");
-        let second = parser.get_next_number().expect("ERROR: Couldn't get seconds");
-
-        let timestamp = get_timestamp(year, month, day, hour, minute, second);
+impl From<SystemTime> for AfDateTime {
+    fn from(value: SystemTime) -> Self {
+        let timestamp = value.duration_since(SystemTime::UNIX_EPOCH).expect("ERROR: Couldn't get systemtime").as_secs() as usize;
+        return AfDateTime::new(timestamp);
+    }
+}
 
-        return AfDateTime {
-            timestamp,
-            year,
-            month,
-            day,
-            hour,
-            minute,
-            second,
-        };
+impl From<String> for AfDateTime {
+    fn from(value: String) -> Self {
+        return AfDateTime::parse(&value).expect("ERROR: Couldn't parse datetime");
     }
 }
 
-fn get_timestamp(year: usize, month: usize, day: usize, hour: usize, minute: usize, second: usize) -> usize {
-    let mut timestamp = 0;
-    timestamp += (year-1970)*86400*365;
-    let mut leap_days_to_add = 0;
-    for i in 1970..year {
-        if i % 4 == 0 && (i % 100 != 0 || i % 400 == 0) {
-            leap_days_to_add += 1;
-        }
+impl From<&str> for AfDateTime {
+    fn from(value: &str) -> Self {
+        return AfDateTime::parse(value).expect("ERROR: Couldn't parse datetime");
     }
+}
 
-    timestamp += leap_days_to_add*86400;
+/// Converts days since 1970-01-01, negative before it, into a year, month
+/// and day, using Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (usize, usize, usize) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    return (year as usize, month as usize, day as usize);
+}
 
-    let days_in_month: [usize; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    let mut month_days = 0;
-    let mut leap_year = false;
-    if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) {
-        leap_year = true;
-    }
-    if month != 1 {
-        for i in 0..month-1 {
-            if i == 1 && leap_year {
-                month_days += 1;
-            }
-            month_days += days_in_month[i];
-        }
-    }
+/// The inverse of `civil_from_days`, days since 1970-01-01 for a date.
+fn days_from_civil(year: usize, month: usize, day: usize) -> i64 {
+    let (year, month, day) = (year as i64, month as i64, day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    return era * 146097 + day_of_era - 719468;
+}
 
-    timestamp += month_days*86400;
+fn is_leap_year(year: usize) -> bool {
+    return year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+}
 
-    timestamp += (day-1)*86400;
-    timestamp += hour*60*60;
-    timestamp += minute*60;
-    timestamp += second;
-    return timestamp;
+fn days_in_month(year: usize, month: usize) -> usize {
+    return match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+}
+
+fn get_timestamp(year: usize, month: usize, day: usize, hour: usize, minute: usize, second: usize) -> i64 {
+    return days_from_civil(year, month, day) * 86400 + (hour * 60 * 60 + minute * 60 + second) as i64;
 }
 
 #[cfg(test)]
@@ -235,8 +442,8 @@ mod tests {
         ];
 
         let inputs = [
-            "1970-01-01T00:00:00", "1970-01-01T00:00:01", "1970-04-31T00:00:00",
-            "1970-12-31T01:30:34", "1971-04-31T00:00:00", "1972-02-29T00:00:00",
+            "1970-01-01T00:00:00", "1970-01-01T00:00:01", "1970-05-01T00:00:00",
+            "1970-12-31T01:30:34", "1971-05-01T00:00:00", "1972-02-29T00:00:00",
             "1972-03-01T00:00:00", "1973-03-01T00:00:00",
             "2023-04-14T05:08:51", "2023-04-10T05:40:17", "2011-08-21T05:37:06"
         ];
@@ -261,6 +468,128 @@ mod tests {
         assert_eq!(result.timestamp, 0);
     }
 
+    #[test]
+    fn parse_invalid_datetime_is_error() {
+        assert!(AfDateTime::parse("yesterday").is_err());
+        assert!(AfDateTime::parse("2023-13-01T00:00:00").is_err());
+        assert!(AfDateTime::parse("2023-04-08T10:17").is_err());
+        assert!(AfDateTime::parse("2023-04-08T10:17:00+2").is_err());
+        assert!(AfDateTime::parse("2023-04-08T10:17:00.").is_err());
+        assert!(AfDateTime::parse("").is_err());
+    }
+
+    #[test]
+    fn parse_day_past_end_of_month_is_error() {
+        assert!(AfDateTime::parse("2023-02-30").is_err());
+        assert!(AfDateTime::parse("2023-02-29").is_err());
+        assert!(AfDateTime::parse("2100-02-29").is_err());
+        assert!(AfDateTime::parse("2023-04-31T00:00:00").is_err());
+        assert!(AfDateTime::parse("2023-04-00").is_err());
+        assert!(AfDateTime::parse("2024-02-29").is_ok());
+        assert!(AfDateTime::parse("2000-02-29").is_ok());
+    }
+
+    #[test]
+    fn parse_dates_before_1970() {
+        let result = AfDateTime::parse("1969-12-31T23:59:59").expect("ERROR: Couldn't parse datetime");
+        assert_eq!(result.get_timestamp(), -1);
+        assert_eq!(result.to_string(), "1969-12-31T23:59:59");
+
+        let moon = AfDateTime::parse("1969-07-20T20:17:40Z").expect("ERROR: Couldn't parse datetime");
+        assert_eq!(moon.get_timestamp(), -14182940);
+        assert_eq!(moon.format("%A %-d %B %Y"), "Sunday 20 July 1969");
+
+        let offset = AfDateTime::parse("1970-01-01T00:30:00+01:00").expect("ERROR: Couldn't parse datetime");
+        assert_eq!(offset.get_timestamp(), -1800);
+        assert_eq!(offset.to_utc().to_string(), "1969-12-31T23:30:00Z");
+
+        let leap = AfDateTime::parse("1600-02-29").expect("ERROR: Couldn't parse datetime");
+        assert_eq!(leap.to_string(), "1600-02-29T00:00:00");
+        assert!(leap < moon);
+    }
+
+    #[test]
+    fn parse_rfc3339_offsets() {
+        let utc = AfDateTime::parse("2023-04-08T08:17:00Z").expect("ERROR: Couldn't parse datetime");
+        let plus_two = AfDateTime::parse("2023-04-08T10:17:00+02:00").expect("ERROR: Couldn't parse datetime");
+        let minus = AfDateTime::parse("2023-04-08T02:47:00-05:30").expect("ERROR: Couldn't parse datetime");
+
+        assert_eq!(plus_two.get_timestamp(), utc.get_timestamp());
+        assert_eq!(minus.get_timestamp(), utc.get_timestamp());
+        assert_eq!(plus_two.to_string(), "2023-04-08T10:17:00+02:00");
+        assert_eq!(minus.to_string(), "2023-04-08T02:47:00-05:30");
+        assert_eq!(utc.to_string(), "2023-04-08T08:17:00Z");
+        assert_eq!(plus_two.to_utc().to_string(), "2023-04-08T08:17:00Z");
+    }
+
+    #[test]
+    fn parse_fractional_seconds() {
+        let result = AfDateTime::parse("2023-04-08T10:17:00.25Z").expect("ERROR: Couldn't parse datetime");
+
+        assert_eq!(result.get_nanosecond(), 250_000_000);
+        assert_eq!(result.to_string(), "2023-04-08T10:17:00.25Z");
+    }
+
+    #[test]
+    fn parse_date_only_and_default_offset() {
+        let result = AfDateTime::parse("2023-04-08").expect("ERROR: Couldn't parse datetime");
+        assert_eq!(result.to_string(), "2023-04-08T00:00:00");
+
+        let local = AfDateTime::parse_with_offset("2023-04-08 10:17:00", 60).expect("ERROR: Couldn't parse datetime");
+        assert_eq!(local.to_string(), "2023-04-08T10:17:00");
+        assert_eq!(local.to_utc().to_string(), "2023-04-08T09:17:00");
+    }
+
+    #[test]
+    fn format_dates() {
+        let result = AfDateTime::parse("2026-10-18T09:05:03+02:00").expect("ERROR: Couldn't parse datetime");
+
+        assert_eq!(result.format("%-d %B %Y"), "18 October 2026");
+        assert_eq!(result.format("%Y-%m-%d"), "2026-10-18");
+        assert_eq!(result.format("%a %e %b %y, %I:%M %p %:z"), "Sun 18 Oct 26, 09:05 AM +02:00");
+        assert_eq!(result.format("%A %H:%M:%S %z %% %q"), "Sunday 09:05:03 +0200 % %q");
+    }
+
+    #[test]
+    fn rfc822_and_rfc3339() {
+        let result = AfDateTime::parse("2023-04-08T10:17:00+02:00").expect("ERROR: Couldn't parse datetime");
+        let naive = AfDateTime::parse("2023-04-08T10:17:00").expect("ERROR: Couldn't parse datetime");
+
+        assert_eq!(result.to_rfc822(), "Sat, 08 Apr 2023 10:17:00 +0200");
+        assert_eq!(result.to_rfc3339(), "2023-04-08T10:17:00+02:00");
+        assert_eq!(naive.to_rfc3339(), "2023-04-08T10:17:00Z");
+    }
+
+    #[test]
+    fn compare_by_instant() {
+        let earlier = AfDateTime::parse("2023-04-08T10:17:00+02:00").expect("ERROR: Couldn't parse datetime");
+        let same = AfDateTime::parse("2023-04-08T08:17:00Z").expect("ERROR: Couldn't parse datetime");
+        let later = AfDateTime::parse("2023-04-08T08:17:00.5Z").expect("ERROR: Couldn't parse datetime");
+
+        assert_eq!(earlier, same);
+        assert!(earlier < later);
+
+        let mut dates = vec![later.clone(), earlier.clone()];
+        dates.sort();
+        assert_eq!(dates, vec![earlier, later]);
+    }
+
+    #[test]
+    fn accessors_and_leap_years() {
+        let result = AfDateTime::new(951782400);
+
+        assert_eq!((result.get_year(), result.get_month(), result.get_day()), (2000, 2, 29));
+        assert_eq!(AfDateTime::new(4107542400).to_string(), "2100-03-01T00:00:00");
+    }
+
+    #[test]
+    fn parse_offset_forms() {
+        assert_eq!(AfDateTime::parse_offset("UTC"), Ok(0));
+        assert_eq!(AfDateTime::parse_offset("+02:00"), Ok(120));
+        assert_eq!(AfDateTime::parse_offset("-0530"), Ok(-330));
+        assert!(AfDateTime::parse_offset("Europe/Oslo").is_err());
+    }
+
     #[test]
     fn mydatetime() {
         let inputs = [1681448931, 1681105217, 1313905026, 0, 1];
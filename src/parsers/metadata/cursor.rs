@@ -0,0 +1,88 @@
+use crate::parsers::metadata::error::MetadataError;
+
+/// Reads front matter one char at a time while keeping track of the line,
+/// so the format parsers can share the flow value syntax in `value.rs`.
+#[derive(Debug)]
+pub(super) struct Cursor<'a> {
+    input: &'a str,
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        return Cursor { input, position: 0, line: 1 };
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        return self.rest().chars().next();
+    }
+
+    pub fn rest(&self) -> &'a str {
+        return &self.input[self.position..];
+    }
+
+    pub fn starts_with(&self, pattern: &str) -> bool {
+        return self.rest().starts_with(pattern);
+    }
+
+    pub fn get_line(&self) -> usize {
+        return self.line;
+    }
+
+    pub fn read_char(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+        }
+        return Some(ch);
+    }
+
+    pub fn read_if(&mut self, predicate: impl Fn(char) -> bool) -> Option<char> {
+        return match self.peek() {
+            Some(ch) if predicate(ch) => self.read_char(),
+            _ => None,
+        };
+    }
+
+    pub fn read_if_eq(&mut self, expected: char) -> bool {
+        return self.read_if(|c| c == expected).is_some();
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.read_char();
+        }
+    }
+
+    /// Skips spaces and tabs, but not newlines.
+    pub fn skip_spaces(&mut self) {
+        while self.read_if(|c| c == ' ' || c == '\t').is_some() {}
+    }
+
+    /// Skips whitespace, newlines and `#` comments.
+    pub fn skip_blank(&mut self) {
+        loop {
+            while self.read_if(char::is_whitespace).is_some() {}
+            if self.peek() != Some('#') {
+                break;
+            }
+            while self.read_if(|c| c != '\n').is_some() {}
+        }
+    }
+
+    /// Expects only spaces and an optional comment until the end of the line.
+    pub fn expect_line_end(&mut self) -> Result<(), MetadataError> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            while self.read_if(|c| c != '\n').is_some() {}
+        }
+        self.read_if_eq('\r');
+
+        return match self.read_char() {
+            None | Some('\n') => Ok(()),
+            Some(c) => Err(MetadataError::syntax(self.line, format!("unexpected character {c:?}"))),
+        };
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataErrorKind {
+    /// The front matter doesn't start with `+++`, `---` or `{`
+    UnknownFormat,
+    /// Malformed TOML, YAML or JSON
+    Syntax(String),
+    /// A known key with a value of the wrong type, e.g. `published: yes`
+    InvalidType { expected: &'static str },
+    /// A date that couldn't be parsed
+    InvalidDate(String),
+    /// A slug that is empty or would leave its directory, e.g. `../x`
+    InvalidSlug,
+}
+
+impl Display for MetadataErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataErrorKind::UnknownFormat => write!(f, "unknown front matter format"),
+            MetadataErrorKind::Syntax(message) => write!(f, "{message}"),
+            MetadataErrorKind::InvalidType { expected } => write!(f, "expected {expected}"),
+            MetadataErrorKind::InvalidDate(message) => write!(f, "invalid date: {message}"),
+            MetadataErrorKind::InvalidSlug => write!(f, "slug can't be empty or contain '/', '\\' or '..'"),
+        }
+    }
+}
+
+/// A front matter error. `line` counts from the start of the content file,
+/// `path` is filled in by the build once it knows which file failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataError {
+    pub kind: MetadataErrorKind,
+    pub path: Option<String>,
+    pub line: usize,
+    pub key: Option<String>,
+}
+
+impl MetadataError {
+    pub fn new(kind: MetadataErrorKind, line: usize) -> Self {
+        return MetadataError { kind, path: None, line, key: None };
+    }
+
+    pub(super) fn syntax(line: usize, message: impl Into<String>) -> Self {
+        return MetadataError::new(MetadataErrorKind::Syntax(message.into()), line);
+    }
+
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        return self;
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        return self;
+    }
+
+    /// Moves the error down by the lines in front of the parsed text, such as
+    /// the opening delimiter.
+    pub(super) fn offset(mut self, lines: usize) -> Self {
+        self.line += lines;
+        return self;
+    }
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{path}:")?;
+        }
+        write!(f, "{}: ", self.line)?;
+        if let Some(key) = &self.key {
+            write!(f, "key \"{key}\": ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use super::{MetadataError, MetadataErrorKind};
+
+    #[test]
+    fn display_with_path_and_key() {
+        let error = MetadataError::new(MetadataErrorKind::InvalidType { expected: "boolean" }, 3)
+            .with_key("published")
+            .with_path("content/index.md");
+
+        assert_eq!(error.to_string(), "content/index.md:3: key \"published\": expected boolean");
+    }
+}
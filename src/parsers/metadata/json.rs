@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::cursor::Cursor;
+use crate::parsers::metadata::error::MetadataError;
+use crate::parsers::metadata::value::{Value, parse_flow};
+
+/// Parses JSON front matter, a single object at the top of the file, into a
+/// table. `null` members are left out.
+pub(super) fn parse(input: &str) -> Result<BTreeMap<String, Value>, MetadataError> {
+    let mut cursor = Cursor::new(input);
+    cursor.skip_blank();
+    if cursor.peek() != Some('{') {
+        return Err(MetadataError::syntax(cursor.get_line(), "JSON front matter must be an object"));
+    }
+
+    let value = parse_flow(&mut cursor, true)?;
+
+    cursor.skip_blank();
+    if let Some(c) = cursor.peek() {
+        return Err(MetadataError::syntax(cursor.get_line(), format!("unexpected character {c:?} after object")));
+    }
+
+    return match value {
+        Some(Value::Table(table)) => Ok(table),
+        _ => Err(MetadataError::syntax(1, "JSON front matter must be an object")),
+    };
+}
+
+/// Returns the length of the object at the start of the input, or `None` if
+/// it is never closed. Braces inside strings are skipped.
+pub(super) fn object_len(input: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in input.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {},
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {},
+        }
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod json_tests {
+    use claim::{assert_err, assert_none};
+
+    use crate::parsers::metadata::value::Value;
+
+    use super::{object_len, parse};
+
+    #[test]
+    fn parse_object() {
+        let input = "{\n\
+                     \x20 \"title\": \"Hello {world}\",\n\
+                     \x20 \"weight\": 3,\n\
+                     \x20 \"tags\": [\"rust\", \"web\"],\n\
+                     \x20 \"params\": { \"hero\": \"/img/hero.png\" },\n\
+                     \x20 \"slug\": null\n\
+                     }";
+
+        let table = parse(input).expect("ERROR: Couldn't parse json");
+
+        assert_eq!(table["title"], Value::String("Hello {world}".to_string()));
+        assert_eq!(table["weight"], Value::Integer(3));
+        assert_eq!(table["params"].as_table().unwrap()["hero"], Value::String("/img/hero.png".to_string()));
+        assert!(!table.contains_key("slug"));
+    }
+
+    #[test]
+    fn parse_not_an_object_is_error() {
+        assert_err!(parse("[1, 2]"));
+        assert_err!(parse("{\"a\": 1} {}"));
+    }
+
+    #[test]
+    fn parse_surrogate_pair_escape() {
+        let table = parse("{\"title\": \"Launch \\ud83d\\ude80\"}").expect("ERROR: Couldn't parse json");
+
+        assert_eq!(table["title"], Value::String("Launch \u{1f680}".to_string()));
+        assert_err!(parse("{\"title\": \"\\u12\"}"));
+    }
+
+    #[test]
+    fn object_len_skips_braces_in_strings() {
+        assert_eq!(object_len("{\"a\": \"}\\\"\"}\n# Body"), Some(12));
+        assert_none!(object_len("{\"a\": 1"));
+    }
+}
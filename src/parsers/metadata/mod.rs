@@ -1,32 +1,145 @@
 mod afdatetime;
+mod cursor;
+mod error;
+mod json;
 mod parser;
-mod lexer;
+mod toml;
+mod value;
+mod yaml;
 
-use std::{fmt::Display, time::SystemTime};
+use std::{collections::{BTreeMap, BTreeSet}, fmt::Display};
 
-use self::afdatetime::AfDateTime;
+pub use self::afdatetime::AfDateTime;
+pub use self::error::{MetadataError, MetadataErrorKind};
+pub use self::parser::Format;
+pub use self::value::Value;
+
+pub static FRONT_MATTER_DELIM: &str = "+++";
+pub static YAML_FRONT_MATTER_DELIM: &str = "---";
 
 #[derive(Debug, PartialEq)]
 pub struct Metadata {
     date: AfDateTime,
     published: bool,
+    /// Pages with a `publishDate` in the future are held back
+    publish_date: Option<AfDateTime>,
+    /// Pages with an `expiryDate` in the past are held back
+    expiry_date: Option<AfDateTime>,
+    lastmod: Option<AfDateTime>,
+    title: Option<String>,
+    description: Option<String>,
+    slug: Option<String>,
+    author: Option<String>,
+    tags: Vec<String>,
+    categories: Vec<String>,
+    weight: i64,
+    aliases: Vec<String>,
+    /// Keys without a dedicated field, e.g. `hero_image: /img/hero.png`
+    params: BTreeMap<String, Value>,
+    /// Every key set in the front matter, as defaulted fields look the same
+    /// as set ones
+    keys: BTreeSet<String>,
 }
 
 impl Metadata {
-    fn new(date: AfDateTime, published: bool) -> Self {
+    pub fn new(date: AfDateTime, published: bool) -> Self {
         return Metadata {
             date,
             published,
+            publish_date: None,
+            expiry_date: None,
+            lastmod: None,
+            title: None,
+            description: None,
+            slug: None,
+            author: None,
+            tags: Vec::new(),
+            categories: Vec::new(),
+            weight: 0,
+            aliases: Vec::new(),
+            params: BTreeMap::new(),
+            keys: BTreeSet::new(),
         };
     }
+
+    /// Parses front matter, delimiters included. `default_date` is used when
+    /// there is no `date` key; the build passes the file's modification time.
+    /// Dates without an offset are in `utc_offset` minutes east of UTC.
+    pub fn parse(input: &str, default_date: AfDateTime, utc_offset: i32) -> Result<Metadata, MetadataError> {
+        return parser::Parser::from(input).with_default_date(default_date).with_utc_offset(utc_offset).parse();
+    }
+
+    pub fn get_date(&self) -> &AfDateTime {
+        return &self.date;
+    }
+
+    pub fn is_published(&self) -> bool {
+        return self.published;
+    }
+
+    pub fn get_publish_date(&self) -> Option<&AfDateTime> {
+        return self.publish_date.as_ref();
+    }
+
+    pub fn get_expiry_date(&self) -> Option<&AfDateTime> {
+        return self.expiry_date.as_ref();
+    }
+
+    /// The `lastmod` key, if set. The build falls back to the file's
+    /// modification time or git history.
+    pub fn get_lastmod(&self) -> Option<&AfDateTime> {
+        return self.lastmod.as_ref();
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        return self.title.as_deref();
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        return self.description.as_deref();
+    }
+
+    pub fn get_slug(&self) -> Option<&str> {
+        return self.slug.as_deref();
+    }
+
+    pub fn get_author(&self) -> Option<&str> {
+        return self.author.as_deref();
+    }
+
+    pub fn get_tags(&self) -> &[String] {
+        return &self.tags;
+    }
+
+    pub fn get_categories(&self) -> &[String] {
+        return &self.categories;
+    }
+
+    pub fn get_weight(&self) -> i64 {
+        return self.weight;
+    }
+
+    pub fn get_aliases(&self) -> &[String] {
+        return &self.aliases;
+    }
+
+    pub fn get_params(&self) -> &BTreeMap<String, Value> {
+        return &self.params;
+    }
+
+    pub fn get_param(&self, key: &str) -> Option<&Value> {
+        return self.params.get(key);
+    }
+
+    /// Whether the front matter set `key`, rather than it being defaulted.
+    pub fn has_key(&self, key: &str) -> bool {
+        return self.keys.contains(key);
+    }
 }
 
 impl Default for Metadata {
     fn default() -> Self {
-        return Metadata {
-            date: AfDateTime::new(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("ERROR: Couldn't get systemtime").as_secs() as usize),
-            published: true,
-        };
+        return Metadata::new(AfDateTime::now(), true);
     }
 }
 
@@ -37,20 +150,105 @@ impl Display for Metadata {
 }
 
 
-impl From<&str> for Metadata {
-    fn from(value: &str) -> Self {
-        let mut parser = parser::Parser::from(value);
-        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+/// Splits a content file into its front matter and the markdown body. The
+/// front matter is TOML between `+++` lines, YAML between `---` lines or a
+/// JSON object starting on the first line. Files without front matter, or
+/// where the front matter is never closed, are all body.
+pub fn split_front_matter(input: &str) -> (Option<&str>, &str) {
+    let delim = match Format::detect(input) {
+        Some(Format::Toml) => FRONT_MATTER_DELIM,
+        Some(Format::Yaml) => YAML_FRONT_MATTER_DELIM,
+        Some(Format::Json) => {
+            let Some(len) = json::object_len(input) else {
+                return (None, input);
+            };
+            // The rest of the line the object closes on belongs to the front matter
+            let end = match input[len..].find('\n') {
+                Some(newline) => len + newline + 1,
+                None => input.len(),
+            };
+            return (Some(&input[..end]), &input[end..]);
+        }
+        None => return (None, input),
+    };
 
-        return metadata;
+    let mut offset = 0;
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        if i > 0 && line.trim_end() == delim {
+            return (Some(&input[..offset]), &input[offset..]);
+        }
     }
+
+    return (None, input);
 }
 
 #[cfg(test)]
 mod metadata_tests {
     use std::time::SystemTime;
 
-    use crate::parsers::metadata::{Metadata, afdatetime::AfDateTime};
+    use crate::parsers::metadata::{Metadata, afdatetime::AfDateTime, split_front_matter};
+
+    #[test]
+    fn split_front_matter_returns_front_matter_and_body() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: true\n\
+                     +++\n\
+                     # Hello";
+
+        let (front_matter, body) = split_front_matter(input);
+
+        assert_eq!(front_matter, Some("+++\ndate: 2023-04-08T10:17:00\npublished: true\n+++\n"));
+        assert_eq!(body, "# Hello");
+    }
+
+    #[test]
+    fn split_front_matter_without_front_matter() {
+        let input = "# Hello\n\
+                     ---";
+
+        let (front_matter, body) = split_front_matter(input);
+
+        assert_eq!(front_matter, None);
+        assert_eq!(body, input);
+    }
+
+    #[test]
+    fn split_front_matter_yaml_and_json() {
+        let (front_matter, body) = split_front_matter("---\ntitle: Hello\n---\n# Hello");
+        assert_eq!(front_matter, Some("---\ntitle: Hello\n---\n"));
+        assert_eq!(body, "# Hello");
+
+        let (front_matter, body) = split_front_matter("{\n  \"title\": \"}\"\n}\n# Hello");
+        assert_eq!(front_matter, Some("{\n  \"title\": \"}\"\n}\n"));
+        assert_eq!(body, "# Hello");
+    }
+
+    #[test]
+    fn split_front_matter_unclosed_is_body() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     # Hello";
+
+        let (front_matter, body) = split_front_matter(input);
+
+        assert_eq!(front_matter, None);
+        assert_eq!(body, input);
+    }
+
+    #[test]
+    fn metadata_new_from_str_without_trailing_newline() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: false\n\
+                     +++";
+
+        let metadata = Metadata::parse(input, AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
+
+        assert!(!metadata.is_published());
+        assert_eq!(metadata.get_date(), &AfDateTime::from("2023-04-08T10:17:00"));
+    }
 
     #[test]
     fn metadata_default() {
@@ -58,7 +256,7 @@ mod metadata_tests {
 
         let datetime = AfDateTime::new(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).expect("ERROR: Couldn't get systemtime").as_secs() as usize);
 
-        assert_eq!(metadata.published, true);
+        assert!(metadata.published);
         assert_eq!(metadata.date, datetime);
     }
 
@@ -69,9 +267,9 @@ mod metadata_tests {
                      published: true\n\
                      +++\n";
 
-        let metadata = Metadata::from(input);
+        let metadata = Metadata::parse(input, AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
 
-        assert_eq!(metadata.published, true);
+        assert!(metadata.published);
         assert_eq!(metadata.date, AfDateTime::from("2023-04-08T10:17:00"));
     }
 }
@@ -1,76 +1,164 @@
-use super::Metadata;
+use std::collections::BTreeMap;
+
+use super::{Metadata, FRONT_MATTER_DELIM, YAML_FRONT_MATTER_DELIM};
 
 use crate::parsers::metadata::afdatetime::AfDateTime;
-use crate::parsers::metadata::lexer::Token;
-use crate::parsers::metadata::lexer::Lexer;
-use crate::parsers::metadata::lexer::TokenType;
+use crate::parsers::metadata::error::{MetadataError, MetadataErrorKind};
+use crate::parsers::metadata::value::Value;
+use crate::parsers::metadata::{json, toml, yaml};
+
+/// The front matter formats, told apart by how the block starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    pub fn detect(input: &str) -> Option<Format> {
+        let first_line = input.lines().next()?.trim_end();
+        return match first_line {
+            _ if first_line == FRONT_MATTER_DELIM => Some(Format::Toml),
+            _ if first_line == YAML_FRONT_MATTER_DELIM => Some(Format::Yaml),
+            _ if first_line.starts_with('{') => Some(Format::Json),
+            _ => None,
+        };
+    }
+}
 
 pub struct Parser {
-    lexer: Lexer,
-    cur_token: Token,
+    input: String,
+    default_date: Option<AfDateTime>,
+    utc_offset: i32,
 }
 
 impl Parser {
     fn new(input: &str) -> Self {
-        let mut lexer = Lexer::new(input).expect("ERROR: Couldn't create lexer from input");
-        let cur_token = lexer.next_token();
-        let parser = Parser {
-            lexer,
-            cur_token, 
+        return Parser { input: input.to_string(), default_date: None, utc_offset: 0 };
+    }
+
+    /// The date used when the front matter has none. Defaults to now.
+    pub fn with_default_date(mut self, date: AfDateTime) -> Self {
+        self.default_date = Some(date);
+        return self;
+    }
+
+    /// The offset, in minutes east of UTC, for dates written without one.
+    pub fn with_utc_offset(mut self, utc_offset: i32) -> Self {
+        self.utc_offset = utc_offset;
+        return self;
+    }
+
+    pub fn parse(&mut self) -> Result<Metadata, MetadataError> {
+        // Errors from the format parsers count from the line after the opening delimiter
+        let table = match Format::detect(&self.input) {
+            Some(Format::Toml) => toml::parse(strip_delimiters(&self.input, FRONT_MATTER_DELIM)).map_err(|e| e.offset(1))?,
+            Some(Format::Yaml) => yaml::parse(strip_delimiters(&self.input, YAML_FRONT_MATTER_DELIM)).map_err(|e| e.offset(1))?,
+            Some(Format::Json) => json::parse(&self.input)?,
+            None => return Err(MetadataError::new(MetadataErrorKind::UnknownFormat, 1)),
         };
 
-        return parser;
-    }
-
-    fn read_next(&mut self) {
-        self.cur_token = self.lexer.next_token();
-    }
-
-    pub fn parse(&mut self) -> Result<Metadata, String> {
-        let mut date: Option<AfDateTime> = None;
-        let mut published: Option<bool> = None;
-        while self.cur_token.token_type != TokenType::EOF {
-            match self.cur_token.token_type {
-                TokenType::Delim => { }
-                TokenType::Key => {
-                    match self.cur_token.literal.as_ref().unwrap().as_str() {
-                        "date" => {
-                            self.read_next();
-                            match self.cur_token.token_type {
-                                TokenType::Value => {
-                                    let str = self.cur_token.literal.clone().unwrap();
-                                    date = Some(AfDateTime::from(str));
-                                }
-                                _ => {
-                                    return Err(format!("ERROR: Unexpected token: {:?}", self.cur_token));
-                                }
-                            }
-                        }
-                        "published" => { 
-                            self.read_next();
-                            match self.cur_token.token_type {
-                                TokenType::Value => {
-                                    let str = self.cur_token.literal.clone().unwrap();
-                                    published = Some(str.parse::<bool>().unwrap());
-                                }
-                                _ => {
-                                    return Err(format!("ERROR: Unexpected token: {:?}", self.cur_token));
-                                }
-                            }
-                        }
-                        _ => {
-                            return Err(format!("ERROR: Unexpected key: {:?}", self.cur_token.literal.as_ref().unwrap()));
-                        }
+        return self.metadata_from_table(table);
+    }
+
+    fn metadata_from_table(&mut self, table: BTreeMap<String, Value>) -> Result<Metadata, MetadataError> {
+        let mut metadata = Metadata::new(self.default_date.take().unwrap_or_else(AfDateTime::now), true);
+
+        for (key, value) in table {
+            match &value {
+                Value::Table(params) if key == "params" => metadata.keys.extend(params.keys().cloned()),
+                _ => {
+                    metadata.keys.insert(key.clone());
+                }
+            }
+
+            let invalid_type = |expected| {
+                return MetadataError::new(MetadataErrorKind::InvalidType { expected }, find_key_line(&self.input, &key)).with_key(&key);
+            };
+            let parse_date = |value: Value| {
+                let date = expect_string(value).ok_or_else(|| invalid_type("string"))?;
+                return AfDateTime::parse_with_offset(&date, self.utc_offset).map_err(|e| {
+                    return MetadataError::new(MetadataErrorKind::InvalidDate(e), find_key_line(&self.input, &key)).with_key(&key);
+                });
+            };
+
+            match key.as_str() {
+                "date" => metadata.date = parse_date(value)?,
+                "publishDate" => metadata.publish_date = Some(parse_date(value)?),
+                "expiryDate" => metadata.expiry_date = Some(parse_date(value)?),
+                "lastmod" => metadata.lastmod = Some(parse_date(value)?),
+                "published" => metadata.published = value.as_bool().ok_or_else(|| invalid_type("boolean"))?,
+                "title" => metadata.title = Some(expect_string(value).ok_or_else(|| invalid_type("string"))?),
+                "description" => metadata.description = Some(expect_string(value).ok_or_else(|| invalid_type("string"))?),
+                "slug" => {
+                    let slug = expect_string(value).ok_or_else(|| invalid_type("string"))?;
+                    if slug.is_empty() || slug.contains(['/', '\\']) || slug.contains("..") {
+                        return Err(MetadataError::new(MetadataErrorKind::InvalidSlug, find_key_line(&self.input, &key)).with_key(&key));
+                    }
+                    metadata.slug = Some(slug);
+                }
+                "author" => metadata.author = Some(expect_string(value).ok_or_else(|| invalid_type("string"))?),
+                "tags" => metadata.tags = expect_string_list(value).ok_or_else(|| invalid_type("list of strings"))?,
+                "categories" => metadata.categories = expect_string_list(value).ok_or_else(|| invalid_type("list of strings"))?,
+                "aliases" => metadata.aliases = expect_string_list(value).ok_or_else(|| invalid_type("list of strings"))?,
+                "weight" => metadata.weight = value.as_integer().ok_or_else(|| invalid_type("integer"))?,
+                // A `[params]` table is merged, so both `hero` and `params.hero` work
+                "params" if matches!(value, Value::Table(_)) => {
+                    if let Value::Table(params) = value {
+                        metadata.params.extend(params);
                     }
                 }
                 _ => {
-                    return Err(format!("ERROR: Unexpected token: {:?}", self.cur_token.token_type));
+                    metadata.params.insert(key, value);
                 }
             }
-            self.read_next();
         }
-        return Ok(Metadata::new(date.unwrap(), published.unwrap()));
+
+        return Ok(metadata);
+    }
+}
+
+/// The line a top level key is set on, counted from the start of the
+/// content file, e.g. `title = `, `title:` or `"title":`.
+fn find_key_line(input: &str, key: &str) -> usize {
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim_start();
+        let rest = line.strip_prefix('"').and_then(|x| x.strip_prefix(key)).and_then(|x| x.strip_prefix('"'))
+            .or_else(|| line.strip_prefix(key));
+        if rest.is_some_and(|x| x.trim_start().starts_with([':', '='])) {
+            return i + 1;
+        }
     }
+    return 1;
+}
+
+/// Removes the opening delimiter line and the closing one, if present.
+fn strip_delimiters<'a>(input: &'a str, delim: &str) -> &'a str {
+    let body = input.split_once('\n').map(|x| x.1).unwrap_or_default();
+    let trimmed = body.trim_end();
+    return match trimmed.strip_suffix(delim) {
+        Some(inner) if inner.is_empty() || inner.ends_with('\n') => inner,
+        _ => body,
+    };
+}
+
+/// Any scalar is accepted where a string is expected, so `title: 2023` is
+/// the title "2023" rather than an error.
+fn expect_string(value: Value) -> Option<String> {
+    return match value {
+        Value::Array(_) | Value::Table(_) => None,
+        Value::String(s) => Some(s),
+        scalar => Some(scalar.to_string()),
+    };
+}
+
+/// A single value is read as a list with one entry, so `tags: rust` works.
+fn expect_string_list(value: Value) -> Option<Vec<String>> {
+    return match value {
+        Value::Array(items) => items.into_iter().map(expect_string).collect(),
+        scalar => Some(vec![expect_string(scalar)?]),
+    };
 }
 
 impl From<&str> for Parser {
@@ -84,6 +172,8 @@ mod parser_tests {
     use claim::assert_err;
 
     use crate::parsers::metadata::afdatetime::AfDateTime;
+    use crate::parsers::metadata::error::{MetadataError, MetadataErrorKind};
+    use crate::parsers::metadata::value::Value;
 
     use super::Parser;
 
@@ -97,14 +187,204 @@ mod parser_tests {
         let mut parser = Parser::new(input);
         let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
 
-        assert_eq!(metadata.published, true);
+        assert!(metadata.published);
         assert_eq!(metadata.date, AfDateTime::from("2023-04-08T10:17:00"));
     }
 
+    #[test]
+    fn parse_extended_front_matter() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: true\n\
+                     title: \"Hello: world\"\n\
+                     description: A first post\n\
+                     slug: hello\n\
+                     author: Jane\n\
+                     tags: [rust, \"static sites\"]\n\
+                     categories: notes\n\
+                     weight: 10\n\
+                     aliases: [/old/hello.html]\n\
+                     hero: /img/hero.png\n\
+                     rating: 4.5\n\
+                     +++\n";
+
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert_eq!(metadata.get_title(), Some("Hello: world"));
+        assert_eq!(metadata.get_description(), Some("A first post"));
+        assert_eq!(metadata.get_slug(), Some("hello"));
+        assert_eq!(metadata.get_author(), Some("Jane"));
+        assert_eq!(metadata.get_tags(), ["rust".to_string(), "static sites".to_string()]);
+        assert_eq!(metadata.get_categories(), ["notes".to_string()]);
+        assert_eq!(metadata.get_weight(), 10);
+        assert_eq!(metadata.get_aliases(), ["/old/hello.html".to_string()]);
+        assert_eq!(metadata.get_param("hero"), Some(&Value::String("/img/hero.png".to_string())));
+        assert_eq!(metadata.get_param("rating"), Some(&Value::Float(4.5)));
+    }
+
+    #[test]
+    fn parse_toml_with_trailing_comments() {
+        let input = "+++\n\
+                     date = 2023-04-08T10:17:00 # c\n\
+                     published = false # draft\n\
+                     weight = 10 # x\n\
+                     +++\n";
+
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert!(!metadata.published);
+        assert_eq!(metadata.get_weight(), 10);
+        assert_eq!(metadata.date, AfDateTime::from("2023-04-08T10:17:00"));
+    }
+
+    #[test]
+    fn parse_wrong_type_is_error() {
+        let input = "+++\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: yes\n\
+                     +++\n";
+        let mut parser = Parser::new(input);
+        let error = parser.parse().expect_err("ERROR: Parsed invalid metadata");
+
+        assert_eq!(error, MetadataError::new(MetadataErrorKind::InvalidType { expected: "boolean" }, 3).with_key("published"));
+    }
+
+    #[test]
+    fn parse_invalid_slug_is_error() {
+        for slug in ["\"../../../pwned\"", "\"a/b\"", "'a\\\\b'", "\"..\"", "\"\""] {
+            let input = format!("+++\ntitle = \"Hello\"\nslug = {slug}\n+++\n");
+            let mut parser = Parser::new(&input);
+            let error = parser.parse().expect_err("ERROR: Parsed invalid slug");
+
+            assert_eq!(error, MetadataError::new(MetadataErrorKind::InvalidSlug, 3).with_key("slug"), "{slug}");
+        }
+    }
+
+    #[test]
+    fn parse_invalid_date_is_error() {
+        let input = "---\n\
+                     title: Hello\n\
+                     date: yesterday\n\
+                     ---\n";
+        let mut parser = Parser::new(input);
+        let error = parser.parse().expect_err("ERROR: Parsed invalid metadata");
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.key.as_deref(), Some("date"));
+        assert!(matches!(error.kind, MetadataErrorKind::InvalidDate(_)), "Not an invalid date error");
+    }
+
+    #[test]
+    fn parse_syntax_error_has_line() {
+        let input = "+++\n\
+                     title = \"Hello\"\n\
+                     tags = [\"a\"\n\
+                     +++\n";
+        let mut parser = Parser::new(input);
+        let error = parser.parse().expect_err("ERROR: Parsed invalid metadata");
+
+        assert_eq!(error.line, 3);
+        assert!(matches!(error.kind, MetadataErrorKind::Syntax(_)), "Not a syntax error");
+    }
+
+    #[test]
+    fn parse_date_uses_utc_offset() {
+        let input = "+++\n\
+                     date = 2023-04-08T10:17:00\n\
+                     +++\n";
+        let mut parser = Parser::new(input).with_utc_offset(120);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert_eq!(metadata.get_date().get_offset(), 120);
+        assert_eq!(metadata.get_date().to_utc().to_string(), "2023-04-08T08:17:00");
+    }
+
+    #[test]
+    fn parse_publish_and_expiry_dates() {
+        let input = "---\n\
+                     publishDate: 2023-04-08T10:17:00Z\n\
+                     expiryDate: 2024-04-08\n\
+                     lastmod: 2023-06-01\n\
+                     ---\n";
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert_eq!(metadata.get_publish_date(), Some(&AfDateTime::from("2023-04-08T10:17:00Z")));
+        assert_eq!(metadata.get_expiry_date(), Some(&AfDateTime::from("2024-04-08")));
+        assert_eq!(metadata.get_lastmod(), Some(&AfDateTime::from("2023-06-01")));
+    }
+
+    #[test]
+    fn parse_missing_fields_use_defaults() {
+        let input = "+++\n\
+                     title = \"Hello\"\n\
+                     +++\n";
+        let mut parser = Parser::new(input).with_default_date(AfDateTime::from("2023-04-08T10:17:00"));
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert!(metadata.is_published());
+        assert_eq!(metadata.get_date(), &AfDateTime::from("2023-04-08T10:17:00"));
+    }
+
+    #[test]
+    fn parse_yaml_front_matter() {
+        let input = "---\n\
+                     date: 2023-04-08T10:17:00\n\
+                     published: true\n\
+                     title: Hello\n\
+                     tags:\n\
+                     \x20 - rust\n\
+                     params:\n\
+                     \x20 hero: /img/hero.png\n\
+                     ---\n";
+
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert_eq!(metadata.get_title(), Some("Hello"));
+        assert_eq!(metadata.get_tags(), ["rust".to_string()]);
+        assert_eq!(metadata.get_param("hero"), Some(&Value::String("/img/hero.png".to_string())));
+    }
+
+    #[test]
+    fn parse_json_front_matter() {
+        let input = "{\n\
+                     \x20 \"date\": \"2023-04-08T10:17:00\",\n\
+                     \x20 \"published\": false,\n\
+                     \x20 \"title\": \"Hello\"\n\
+                     }\n";
+
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert!(!metadata.is_published());
+        assert_eq!(metadata.get_title(), Some("Hello"));
+    }
+
+    #[test]
+    fn parse_toml_front_matter() {
+        let input = "+++\n\
+                     date = 2023-04-08T10:17:00\n\
+                     published = true\n\
+                     title = \"Hello\"\n\
+                     [params]\n\
+                     hero = \"/img/hero.png\"\n\
+                     +++\n";
+
+        let mut parser = Parser::new(input);
+        let metadata = parser.parse().expect("ERROR: Couldn't parse metadata");
+
+        assert_eq!(metadata.get_title(), Some("Hello"));
+        assert_eq!(metadata.get_date(), &AfDateTime::from("2023-04-08T10:17:00"));
+        assert_eq!(metadata.get_param("hero"), Some(&Value::String("/img/hero.png".to_string())));
+    }
+
     #[test]
     fn parse_error() {
         let input = "++=\n";
         let mut parser = Parser::new(input);
-        assert_err!(parser.parse(), "ERROR: Unexpected token: Eq");
+        assert_err!(parser.parse(), "Unknown front matter format");
     }
 }
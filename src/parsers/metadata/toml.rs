@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::cursor::Cursor;
+use crate::parsers::metadata::error::MetadataError;
+use crate::parsers::metadata::value::{Value, parse_flow, parse_key};
+
+/// Parses the TOML front matter between the `+++` lines into a table.
+///
+/// Supports `key = value`, dotted keys, `[tables]`, `[[arrays.of.tables]]`,
+/// inline tables, arrays spanning lines and `"""` strings. The older
+/// `key: value` lines are still accepted.
+pub(super) fn parse(input: &str) -> Result<BTreeMap<String, Value>, MetadataError> {
+    let mut root = BTreeMap::new();
+    let mut current_path: Vec<String> = Vec::new();
+    // `[table]` headers seen so far, each table may only be defined once
+    let mut defined: Vec<Vec<String>> = Vec::new();
+    let mut cursor = Cursor::new(input);
+
+    loop {
+        cursor.skip_blank();
+        let line = cursor.get_line();
+        match cursor.peek() {
+            None => break,
+            Some('[') => {
+                let is_array = cursor.starts_with("[[");
+                cursor.skip(if is_array { 2 } else { 1 });
+                cursor.skip_spaces();
+                let path = parse_key_path(&mut cursor)?;
+                cursor.skip_spaces();
+                let closing = if is_array { "]]" } else { "]" };
+                if !cursor.starts_with(closing) {
+                    return Err(MetadataError::syntax(line, format!("expected \"{closing}\"")));
+                }
+                cursor.skip(closing.len());
+                cursor.expect_line_end()?;
+
+                let (last, parents) = path.split_last().expect("ERROR: Key path is empty");
+                let parent = table_at(&mut root, parents, line)?;
+                if is_array {
+                    // Every `[[x]]` entry starts fresh, so its subtables can be defined again
+                    defined.retain(|x| !x.starts_with(&path));
+                    let entry = parent.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                    match entry {
+                        Value::Array(items) => items.push(Value::Table(BTreeMap::new())),
+                        _ => return Err(MetadataError::syntax(line, "not an array of tables").with_key(last)),
+                    }
+                } else {
+                    if defined.contains(&path) {
+                        return Err(MetadataError::syntax(line, "table defined twice").with_key(&path.join(".")));
+                    }
+                    table_at(&mut root, &path, line)?;
+                    defined.push(path.clone());
+                }
+                current_path = path;
+            }
+            Some(_) => {
+                let path = parse_key_path(&mut cursor)?;
+                cursor.skip_spaces();
+                if !cursor.read_if_eq('=') && !cursor.read_if_eq(':') {
+                    return Err(MetadataError::syntax(line, "expected '='").with_key(&path.join(".")));
+                }
+
+                let value = parse_flow(&mut cursor, false)?;
+                cursor.expect_line_end()?;
+
+                let (last, parents) = path.split_last().expect("ERROR: Key path is empty");
+                let full_path = [current_path.as_slice(), parents].concat();
+                let table = table_at(&mut root, &full_path, line)?;
+                if table.contains_key(last) {
+                    return Err(MetadataError::syntax(line, "duplicate key").with_key(last));
+                }
+                if let Some(value) = value {
+                    table.insert(last.clone(), value);
+                }
+            }
+        }
+    }
+
+    return Ok(root);
+}
+
+/// Reads `a.b."c d"` into its parts.
+fn parse_key_path(cursor: &mut Cursor) -> Result<Vec<String>, MetadataError> {
+    let mut path = vec![parse_key(cursor)?];
+    loop {
+        cursor.skip_spaces();
+        if !cursor.read_if_eq('.') {
+            break;
+        }
+        cursor.skip_spaces();
+        path.push(parse_key(cursor)?);
+    }
+    return Ok(path);
+}
+
+/// Walks down the path, creating missing tables. An array of tables is
+/// entered through its last entry, which is where `[[x]]` keys belong.
+fn table_at<'a>(root: &'a mut BTreeMap<String, Value>, path: &[String], line: usize) -> Result<&'a mut BTreeMap<String, Value>, MetadataError> {
+    let mut table = root;
+    for key in path {
+        let entry = table.entry(key.clone()).or_insert_with(|| Value::Table(BTreeMap::new()));
+        let entry = match entry {
+            Value::Array(items) => items.last_mut().ok_or_else(|| MetadataError::syntax(line, "not a table").with_key(key))?,
+            other => other,
+        };
+        table = match entry {
+            Value::Table(inner) => inner,
+            _ => return Err(MetadataError::syntax(line, "not a table").with_key(key)),
+        };
+    }
+    return Ok(table);
+}
+
+#[cfg(test)]
+mod toml_tests {
+    use claim::assert_err;
+
+    use crate::parsers::metadata::value::Value;
+
+    use super::parse;
+
+    #[test]
+    fn parse_keys_and_tables() {
+        let input = "title = \"Hello\" # a comment\n\
+                     tags = [\n  \"rust\",\n  \"web\",\n]\n\
+                     author.name = \"Jane\"\n\
+                     \n\
+                     [params]\n\
+                     hero = \"/img/hero.png\"\n\
+                     \n\
+                     [[menu.main]]\n\
+                     name = \"Home\"\n\
+                     [[menu.main]]\n\
+                     name = \"Posts\"\n";
+
+        let table = parse(input).expect("ERROR: Couldn't parse toml");
+
+        assert_eq!(table["title"], Value::String("Hello".to_string()));
+        assert_eq!(table["tags"], Value::Array(vec![Value::String("rust".to_string()), Value::String("web".to_string())]));
+        assert_eq!(table["author"].as_table().unwrap()["name"], Value::String("Jane".to_string()));
+        assert_eq!(table["params"].as_table().unwrap()["hero"], Value::String("/img/hero.png".to_string()));
+        let main = table["menu"].as_table().unwrap()["main"].as_array().unwrap();
+        assert_eq!(main.len(), 2);
+        assert_eq!(main[1].as_table().unwrap()["name"], Value::String("Posts".to_string()));
+    }
+
+    #[test]
+    fn parse_bare_values_with_comments() {
+        let input = "weight = 10 # x\n\
+                     published = false # draft\n\
+                     date = 2023-04-08T10:17:00 # c\n\
+                     tags = [rust, web] # list\n";
+
+        let table = parse(input).expect("ERROR: Couldn't parse toml");
+
+        assert_eq!(table["weight"], Value::Integer(10));
+        assert_eq!(table["published"], Value::Boolean(false));
+        assert_eq!(table["date"], Value::String("2023-04-08T10:17:00".to_string()));
+        assert_eq!(table["tags"], Value::Array(vec![Value::String("rust".to_string()), Value::String("web".to_string())]));
+    }
+
+    #[test]
+    fn parse_multiline_string_and_legacy_lines() {
+        let input = "summary = \"\"\"\nFirst line\nSecond line\"\"\"\n\
+                     date: 2023-04-08T10:17:00\n";
+
+        let table = parse(input).expect("ERROR: Couldn't parse toml");
+
+        assert_eq!(table["summary"], Value::String("First line\nSecond line".to_string()));
+        assert_eq!(table["date"], Value::String("2023-04-08T10:17:00".to_string()));
+    }
+
+    #[test]
+    fn parse_duplicate_key_is_error() {
+        assert_err!(parse("title = \"a\"\ntitle = \"b\"\n"));
+    }
+
+    #[test]
+    fn parse_table_defined_twice_is_error() {
+        let error = parse("[a]\nx = 1\n\n[a]\ny = 2\n").expect_err("ERROR: Expected an error");
+        assert_eq!((error.line, error.key.as_deref()), (4, Some("a")));
+
+        assert_err!(parse("[a.b]\n[a.b]\n"));
+        assert!(parse("[a]\n[a.b]\n[c]\n").is_ok());
+        assert!(parse("[[items]]\n[items.meta]\n[[items]]\n[items.meta]\n").is_ok());
+    }
+
+    #[test]
+    fn parse_integer_out_of_range_is_error() {
+        let error = parse("weight = 9223372036854775808\n").expect_err("ERROR: Expected an error");
+        assert_eq!(error.line, 1);
+
+        assert_err!(parse("weight = [-9223372036854775809]\n"));
+        assert_eq!(parse("weight = -9223372036854775808\n").expect("ERROR: Couldn't parse toml")["weight"], Value::Integer(i64::MIN));
+        assert_eq!(parse("ratio = 1.5e300\n").expect("ERROR: Couldn't parse toml")["ratio"], Value::Float(1.5e300));
+    }
+
+    #[test]
+    fn parse_trailing_garbage_is_error() {
+        assert_err!(parse("tags = [\"a\"] b\n"));
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::parsers::metadata::cursor::Cursor;
+use crate::parsers::metadata::error::MetadataError;
+
+/// A front matter value. Values that are not quoted, numbers, booleans,
+/// arrays or tables are kept as plain strings, so `title: Hello world` works
+/// unquoted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn parse(input: &str) -> Result<Value, MetadataError> {
+        let mut cursor = Cursor::new(input.trim());
+        let value = parse_flow(&mut cursor, false)?;
+
+        cursor.skip_blank();
+        if let Some(c) = cursor.peek() {
+            return Err(MetadataError::syntax(cursor.get_line(), format!("unexpected character {c:?} after value")));
+        }
+
+        return value.ok_or_else(|| MetadataError::syntax(1, "value is null"));
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        };
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        return match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        };
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        return match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        };
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        return match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        };
+    }
+
+    pub fn as_table(&self) -> Option<&BTreeMap<String, Value>> {
+        return match self {
+            Value::Table(table) => Some(table),
+            _ => None,
+        };
+    }
+}
+
+/// Parses a value in the inline syntax shared by TOML, YAML flow collections
+/// and JSON: quoted strings, `[arrays]`, `{ key = value }` tables and bare
+/// scalars. Returns `None` for `null`. Bare values outside a collection run
+/// to the end of the line or a `#` comment after whitespace, like `10 # x`.
+pub(super) fn parse_flow(cursor: &mut Cursor, nested: bool) -> Result<Option<Value>, MetadataError> {
+    if nested {
+        cursor.skip_blank();
+    } else {
+        cursor.skip_spaces();
+    }
+
+    if cursor.starts_with("\"\"\"") || cursor.starts_with("'''") {
+        return Ok(Some(Value::String(parse_multiline(cursor)?)));
+    }
+
+    return match cursor.peek() {
+        Some('"') | Some('\'') => Ok(Some(Value::String(parse_quoted(cursor)?))),
+        Some('[') => parse_array(cursor).map(Some),
+        Some('{') => parse_table(cursor).map(Some),
+        _ => {
+            let line = cursor.get_line();
+            let mut literal = String::new();
+            loop {
+                let rest = cursor.rest();
+                if rest.starts_with([' ', '\t']) && rest.trim_start_matches([' ', '\t']).starts_with('#') {
+                    break;
+                }
+                match cursor.read_if(|c| c != '\n' && (!nested || !matches!(c, ',' | ']' | '}'))) {
+                    Some(c) => literal.push(c),
+                    None => break,
+                }
+            }
+            parse_bare(literal.trim(), line)
+        }
+    };
+}
+
+pub(super) fn parse_quoted(cursor: &mut Cursor) -> Result<String, MetadataError> {
+    let line = cursor.get_line();
+    let quote = cursor.read_char().expect("ERROR: Expected quote");
+    let mut literal = String::new();
+
+    loop {
+        match cursor.read_char() {
+            None | Some('\n') => return Err(MetadataError::syntax(line, "unclosed string")),
+            // YAML escapes a single quote by doubling it
+            Some('\'') if quote == '\'' && cursor.read_if_eq('\'') => literal.push('\''),
+            Some(c) if c == quote => break,
+            // Single quoted strings are literal, like in TOML
+            Some('\\') if quote == '"' => literal.push(parse_escape(cursor, line)?),
+            Some(c) => literal.push(c),
+        }
+    }
+
+    return Ok(literal);
+}
+
+/// TOML's `"""` and `'''` strings. A newline right after the opening
+/// quotes is not part of the string.
+fn parse_multiline(cursor: &mut Cursor) -> Result<String, MetadataError> {
+    let line = cursor.get_line();
+    let quote = cursor.read_char().expect("ERROR: Expected quote");
+    cursor.skip(2);
+    let delim = quote.to_string().repeat(3);
+    cursor.read_if_eq('\r');
+    cursor.read_if_eq('\n');
+
+    let mut literal = String::new();
+    while !cursor.starts_with(&delim) {
+        match cursor.read_char() {
+            None => return Err(MetadataError::syntax(line, "unclosed string")),
+            Some('\\') if quote == '"' => literal.push(parse_escape(cursor, line)?),
+            Some(c) => literal.push(c),
+        }
+    }
+    cursor.skip(3);
+
+    return Ok(literal);
+}
+
+fn parse_escape(cursor: &mut Cursor, line: usize) -> Result<char, MetadataError> {
+    return match cursor.read_char() {
+        Some('n') => Ok('\n'),
+        Some('t') => Ok('\t'),
+        Some('r') => Ok('\r'),
+        Some('u') => {
+            let code = parse_hex4(cursor, line)?;
+            // Characters outside the BMP are written as a UTF-16 surrogate
+            // pair, e.g. `\ud83d\ude80`
+            let code = match code {
+                0xD800..=0xDBFF if cursor.starts_with("\\u") => {
+                    cursor.skip(2);
+                    match parse_hex4(cursor, line)? {
+                        low @ 0xDC00..=0xDFFF => 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00),
+                        low => return Err(MetadataError::syntax(line, format!("invalid unicode escape \\u{code:04x}\\u{low:04x}"))),
+                    }
+                }
+                _ => code,
+            };
+            char::from_u32(code).ok_or_else(|| MetadataError::syntax(line, format!("invalid unicode escape \\u{code:04x}")))
+        }
+        Some(c @ ('"' | '\\' | '/')) => Ok(c),
+        Some(c) => Err(MetadataError::syntax(line, format!("unknown escape \\{c}"))),
+        None => Err(MetadataError::syntax(line, "unclosed string")),
+    };
+}
+
+/// Reads the 4 hex digits of a `\u` escape.
+fn parse_hex4(cursor: &mut Cursor, line: usize) -> Result<u32, MetadataError> {
+    let mut hex = String::new();
+    while hex.len() < 4 {
+        match cursor.read_if(|c| c.is_ascii_hexdigit()) {
+            Some(c) => hex.push(c),
+            None => return Err(MetadataError::syntax(line, format!("invalid unicode escape \\u{hex}, expected 4 hex digits"))),
+        }
+    }
+    return Ok(u32::from_str_radix(&hex, 16).expect("ERROR: Checked hex digits"));
+}
+
+fn parse_array(cursor: &mut Cursor) -> Result<Value, MetadataError> {
+    let line = cursor.get_line();
+    cursor.read_char();
+    let mut items = Vec::new();
+
+    loop {
+        cursor.skip_blank();
+        if cursor.read_if_eq(']') {
+            break;
+        }
+
+        if let Some(item) = parse_flow(cursor, true)? {
+            items.push(item);
+        }
+
+        cursor.skip_blank();
+        match cursor.read_char() {
+            Some(',') => {},
+            Some(']') => break,
+            Some(c) => return Err(MetadataError::syntax(cursor.get_line(), format!("expected ',' or ']' in array, found {c:?}"))),
+            None => return Err(MetadataError::syntax(line, "unclosed array")),
+        }
+    }
+
+    return Ok(Value::Array(items));
+}
+
+/// Inline tables accept both `key = value` and `key: value`, which covers
+/// TOML inline tables, YAML flow mappings and JSON objects.
+fn parse_table(cursor: &mut Cursor) -> Result<Value, MetadataError> {
+    let line = cursor.get_line();
+    cursor.read_char();
+    let mut table = BTreeMap::new();
+
+    loop {
+        cursor.skip_blank();
+        if cursor.read_if_eq('}') {
+            break;
+        }
+
+        let key = parse_key(cursor)?;
+        cursor.skip_spaces();
+        if !cursor.read_if_eq('=') && !cursor.read_if_eq(':') {
+            return Err(MetadataError::syntax(cursor.get_line(), "expected '=' or ':'").with_key(&key));
+        }
+        if let Some(value) = parse_flow(cursor, true)? {
+            table.insert(key, value);
+        }
+
+        cursor.skip_blank();
+        match cursor.read_char() {
+            Some(',') => {},
+            Some('}') => break,
+            Some(c) => return Err(MetadataError::syntax(cursor.get_line(), format!("expected ',' or '}}' in table, found {c:?}"))),
+            None => return Err(MetadataError::syntax(line, "unclosed table")),
+        }
+    }
+
+    return Ok(Value::Table(table));
+}
+
+/// Reads a quoted key or a bare key made of letters, digits, `_` and `-`.
+pub(super) fn parse_key(cursor: &mut Cursor) -> Result<String, MetadataError> {
+    if matches!(cursor.peek(), Some('"') | Some('\'')) {
+        return parse_quoted(cursor);
+    }
+
+    let mut key = String::new();
+    while let Some(c) = cursor.read_if(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        key.push(c);
+    }
+
+    if key.is_empty() {
+        return Err(MetadataError::syntax(cursor.get_line(), "expected key"));
+    }
+    return Ok(key);
+}
+
+pub(super) fn parse_bare(literal: &str, line: usize) -> Result<Option<Value>, MetadataError> {
+    match literal {
+        "null" => return Ok(None),
+        "true" => return Ok(Some(Value::Boolean(true))),
+        "false" => return Ok(Some(Value::Boolean(false))),
+        _ => {},
+    }
+
+    // f64 also accepts words like "inf" and "NaN", which should stay strings
+    let digits = literal.strip_prefix(['-', '+']).unwrap_or(literal);
+    if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        if let Ok(integer) = literal.parse::<i64>() {
+            return Ok(Some(Value::Integer(integer)));
+        }
+        // Too big for an i64, rather than silently losing precision as a float
+        if digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(MetadataError::syntax(line, format!("integer {literal} is out of range")));
+        }
+        if let Ok(float) = literal.parse::<f64>() {
+            return Ok(Some(Value::Float(float)));
+        }
+    }
+
+    return Ok(Some(Value::String(literal.to_string())));
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{s}"),
+            Value::Integer(i) => write!(f, "{i}"),
+            Value::Float(n) => write!(f, "{n}"),
+            Value::Boolean(b) => write!(f, "{b}"),
+            Value::Array(items) => {
+                let items = items.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+                write!(f, "{}", items.join(", "))
+            }
+            Value::Table(table) => {
+                let pairs = table.iter().map(|(k, v)| format!("{k}: {v}")).collect::<Vec<String>>();
+                write!(f, "{}", pairs.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod value_tests {
+    use std::collections::BTreeMap;
+
+    use claim::assert_err;
+
+    use super::Value;
+
+    #[test]
+    fn parse_scalars() {
+        assert_eq!(Value::parse("true"), Ok(Value::Boolean(true)));
+        assert_eq!(Value::parse("-12"), Ok(Value::Integer(-12)));
+        assert_eq!(Value::parse("1.5"), Ok(Value::Float(1.5)));
+        assert_eq!(Value::parse("inf"), Ok(Value::String("inf".to_string())));
+        assert_eq!(Value::parse("Hello world"), Ok(Value::String("Hello world".to_string())));
+        assert_eq!(Value::parse("2023-04-08T10:17:00"), Ok(Value::String("2023-04-08T10:17:00".to_string())));
+    }
+
+    #[test]
+    fn parse_scalars_with_comment() {
+        assert_eq!(Value::parse("10 # x"), Ok(Value::Integer(10)));
+        assert_eq!(Value::parse("false\t# draft"), Ok(Value::Boolean(false)));
+        assert_eq!(Value::parse("2023-04-08T10:17:00 # c"), Ok(Value::String("2023-04-08T10:17:00".to_string())));
+        assert_eq!(Value::parse("C# and F#"), Ok(Value::String("C# and F#".to_string())));
+        assert_eq!(Value::parse("[1 # one\n, 2]"), Ok(Value::Array(vec![Value::Integer(1), Value::Integer(2)])));
+    }
+
+    #[test]
+    fn parse_quoted_strings() {
+        assert_eq!(Value::parse("\"a \\\"b\\\", c\""), Ok(Value::String("a \"b\", c".to_string())));
+        assert_eq!(Value::parse("'C:\\path'"), Ok(Value::String("C:\\path".to_string())));
+        assert_eq!(Value::parse("\"true\""), Ok(Value::String("true".to_string())));
+    }
+
+    #[test]
+    fn parse_arrays() {
+        let expected = Value::Array(vec![
+            Value::String("rust".to_string()),
+            Value::String("a, b".to_string()),
+            Value::Integer(3),
+            Value::Array(vec![Value::Boolean(false)]),
+        ]);
+
+        assert_eq!(Value::parse("[rust, \"a, b\", 3, [false]]"), Ok(expected));
+        assert_eq!(Value::parse("[]"), Ok(Value::Array(vec![])));
+    }
+
+    #[test]
+    fn parse_invalid_values() {
+        assert_err!(Value::parse("\"unclosed"));
+        assert_err!(Value::parse("[a, b"));
+        assert_err!(Value::parse("[a] b"));
+        assert_err!(Value::parse("null"));
+    }
+
+    #[test]
+    fn parse_inline_tables() {
+        let mut expected = BTreeMap::new();
+        expected.insert("name".to_string(), Value::String("Jane".to_string()));
+        expected.insert("links".to_string(), Value::Array(vec![Value::Integer(1), Value::Integer(2)]));
+
+        assert_eq!(Value::parse("{ name = \"Jane\", links = [1, 2] }"), Ok(Value::Table(expected.clone())));
+        assert_eq!(Value::parse("{\"name\": \"Jane\", \"links\": [1, 2], \"empty\": null}"), Ok(Value::Table(expected)));
+    }
+
+    #[test]
+    fn parse_multiline_strings() {
+        assert_eq!(Value::parse("\"\"\"\nfirst\nsecond\"\"\""), Ok(Value::String("first\nsecond".to_string())));
+        assert_eq!(Value::parse("'''C:\\\n'''"), Ok(Value::String("C:\\\n".to_string())));
+        assert_eq!(Value::parse("'it''s'"), Ok(Value::String("it's".to_string())));
+        assert_eq!(Value::parse("\"\\u00e9\""), Ok(Value::String("\u{e9}".to_string())));
+    }
+
+    #[test]
+    fn parse_unicode_escapes() {
+        assert_eq!(Value::parse("\"\\ud83d\\ude80\""), Ok(Value::String("\u{1f680}".to_string())));
+        assert_eq!(Value::parse("\"\\u00e9t\\u00E9\""), Ok(Value::String("\u{e9}t\u{e9}".to_string())));
+        assert_err!(Value::parse("\"\\u12\""));
+        assert_err!(Value::parse("\"\\u12"));
+        assert_err!(Value::parse("\"\\ud83d\""));
+        assert_err!(Value::parse("\"\\ud83d\\u0041\""));
+        assert_err!(Value::parse("\"\\ude80\""));
+    }
+}
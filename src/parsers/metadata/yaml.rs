@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::cursor::Cursor;
+use crate::parsers::metadata::error::MetadataError;
+use crate::parsers::metadata::value::{Value, parse_bare, parse_flow, parse_quoted};
+
+/// Parses the YAML front matter between the `---` lines into a table.
+///
+/// This is the subset front matter needs: nested mappings by indentation,
+/// `- item` lists (of scalars or mappings), `|` and `>` block scalars,
+/// comments, quoted strings and single line `[flow]` and `{flow}` values.
+pub(super) fn parse(input: &str) -> Result<BTreeMap<String, Value>, MetadataError> {
+    let mut parser = YamlParser {
+        lines: input.lines().map(|x| x.to_string()).collect(),
+        index: 0,
+    };
+
+    let table = match parser.peek() {
+        Some((0, content)) if is_sequence_item(&content) => return Err(MetadataError::syntax(parser.index + 1, "front matter must be a mapping, found a list")),
+        Some((0, _)) => parser.parse_mapping(0)?,
+        Some((_, _)) => return Err(MetadataError::syntax(parser.index + 1, "unexpected indentation")),
+        None => BTreeMap::new(),
+    };
+
+    if parser.peek().is_some() {
+        return Err(MetadataError::syntax(parser.index + 1, "unexpected indentation"));
+    }
+    return Ok(table);
+}
+
+struct YamlParser {
+    lines: Vec<String>,
+    index: usize,
+}
+
+impl YamlParser {
+    /// Skips blank and comment lines and returns the indent and content of
+    /// the next line without consuming it.
+    fn peek(&mut self) -> Option<(usize, String)> {
+        while self.index < self.lines.len() {
+            let content = self.lines[self.index].trim();
+            if !content.is_empty() && !content.starts_with('#') {
+                break;
+            }
+            self.index += 1;
+        }
+
+        let line = self.lines.get(self.index)?;
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        return Some((indent, line.trim().to_string()));
+    }
+
+    fn parse_block(&mut self, indent: usize) -> Result<Value, MetadataError> {
+        return match self.peek() {
+            Some((_, content)) if is_sequence_item(&content) => Ok(Value::Array(self.parse_sequence(indent)?)),
+            _ => Ok(Value::Table(self.parse_mapping(indent)?)),
+        };
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<BTreeMap<String, Value>, MetadataError> {
+        let mut table = BTreeMap::new();
+
+        while let Some((line_indent, content)) = self.peek() {
+            let line = self.index + 1;
+            if line_indent < indent || (line_indent == indent && is_sequence_item(&content)) {
+                break;
+            }
+            if line_indent > indent {
+                return Err(MetadataError::syntax(line, "unexpected indentation"));
+            }
+
+            let Some((key, rest)) = split_key(&content) else {
+                return Err(MetadataError::syntax(line, "expected \"key: value\""));
+            };
+            let rest = rest.to_string();
+            self.index += 1;
+
+            let value = if rest.is_empty() || rest.starts_with('#') {
+                match self.peek() {
+                    Some((next_indent, _)) if next_indent > indent => Some(self.parse_block(next_indent)?),
+                    Some((next_indent, next)) if next_indent == indent && is_sequence_item(&next) => Some(Value::Array(self.parse_sequence(indent)?)),
+                    _ => None,
+                }
+            } else if rest.starts_with('|') || rest.starts_with('>') {
+                Some(Value::String(self.parse_block_scalar(indent, &rest)))
+            } else {
+                parse_scalar(&rest, line)?
+            };
+
+            if table.contains_key(&key) {
+                return Err(MetadataError::syntax(line, "duplicate key").with_key(&key));
+            }
+            if let Some(value) = value {
+                table.insert(key, value);
+            }
+        }
+
+        return Ok(table);
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<Vec<Value>, MetadataError> {
+        let mut items = Vec::new();
+
+        while let Some((line_indent, content)) = self.peek() {
+            let line = self.index + 1;
+            if line_indent != indent || !is_sequence_item(&content) {
+                if line_indent > indent {
+                    return Err(MetadataError::syntax(line, "unexpected indentation"));
+                }
+                break;
+            }
+
+            let rest = &content[1..];
+            let item_indent = indent + 1 + (rest.len() - rest.trim_start().len());
+            let item = rest.trim().to_string();
+            if item.is_empty() {
+                self.index += 1;
+                match self.peek() {
+                    Some((next_indent, _)) if next_indent > indent => items.push(self.parse_block(next_indent)?),
+                    _ => {},
+                }
+            } else if split_key(&item).is_some() {
+                // `- key: value` starts a mapping indented to where the key is
+                self.lines[self.index] = format!("{}{item}", " ".repeat(item_indent));
+                items.push(Value::Table(self.parse_mapping(item_indent)?));
+            } else {
+                self.index += 1;
+                if let Some(value) = parse_scalar(&item, line)? {
+                    items.push(value);
+                }
+            }
+        }
+
+        return Ok(items);
+    }
+
+    /// Reads the lines of a `|` (literal) or `>` (folded) block scalar,
+    /// with `-` and `+` chomping indicators.
+    fn parse_block_scalar(&mut self, indent: usize, header: &str) -> String {
+        let folded = header.starts_with('>');
+        let chomping = header[1..].trim().chars().next();
+
+        let mut lines = Vec::new();
+        while let Some(line) = self.lines.get(self.index) {
+            let line_indent = line.len() - line.trim_start_matches(' ').len();
+            if !line.trim().is_empty() && line_indent <= indent {
+                break;
+            }
+            lines.push(line.clone());
+            self.index += 1;
+        }
+
+        let mut trailing = 0;
+        while lines.last().is_some_and(|x| x.trim().is_empty()) {
+            lines.pop();
+            trailing += 1;
+        }
+
+        let content_indent = lines.iter()
+            .filter(|x| !x.trim().is_empty())
+            .map(|x| x.len() - x.trim_start_matches(' ').len())
+            .min()
+            .unwrap_or(0);
+        let lines = lines.iter().map(|x| x.get(content_indent..).unwrap_or("").trim_end()).collect::<Vec<&str>>();
+
+        let mut body = String::new();
+        if folded {
+            for line in lines {
+                if line.is_empty() {
+                    body.push('\n');
+                    continue;
+                }
+                if !body.is_empty() && !body.ends_with('\n') {
+                    body.push(' ');
+                }
+                body.push_str(line);
+            }
+        } else {
+            body = lines.join("\n");
+        }
+
+        return match chomping {
+            Some('-') => body,
+            Some('+') => body + &"\n".repeat(trailing + 1),
+            _ => body + "\n",
+        };
+    }
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    return content == "-" || content.starts_with("- ");
+}
+
+/// Splits `key: value` into the key and the rest of the line, or returns
+/// `None` if the content is not a mapping entry. The key may be quoted.
+fn split_key(content: &str) -> Option<(String, &str)> {
+    if content.starts_with('"') || content.starts_with('\'') {
+        let mut cursor = Cursor::new(content);
+        let key = parse_quoted(&mut cursor).ok()?;
+        cursor.skip_spaces();
+        let rest = cursor.rest().strip_prefix(':')?;
+        if !rest.is_empty() && !rest.starts_with(' ') {
+            return None;
+        }
+        return Some((key, rest.trim()));
+    }
+    if content.starts_with('[') || content.starts_with('{') {
+        return None;
+    }
+
+    let end = content.find(": ").or_else(|| content.strip_suffix(':').map(|x| x.len()))?;
+    return Some((content[..end].trim().to_string(), content[end + 1..].trim()));
+}
+
+/// A value on the same line as its key or list marker.
+fn parse_scalar(text: &str, line: usize) -> Result<Option<Value>, MetadataError> {
+    if text.starts_with(['"', '\'', '[', '{']) {
+        let mut cursor = Cursor::new(text);
+        let value = parse_flow(&mut cursor, false).map_err(|e| e.offset(line - 1))?;
+        cursor.expect_line_end().map_err(|e| e.offset(line - 1))?;
+        return Ok(value);
+    }
+
+    let text = match text.find(" #") {
+        Some(comment) => text[..comment].trim_end(),
+        None => text,
+    };
+    if text == "~" {
+        return Ok(None);
+    }
+    return parse_bare(text, line);
+}
+
+#[cfg(test)]
+mod yaml_tests {
+    use claim::assert_err;
+
+    use crate::parsers::metadata::value::Value;
+
+    use super::parse;
+
+    #[test]
+    fn parse_nested_mappings_and_lists() {
+        let input = "title: \"Hello: world\"\n\
+                     draft: false # not yet\n\
+                     tags:\n\
+                     - rust\n\
+                     - web\n\
+                     categories: [notes, \"til\"]\n\
+                     params:\n\
+                     \x20 hero: /img/hero.png\n\
+                     \x20 links:\n\
+                     \x20   - name: Home\n\
+                     \x20     url: /\n\
+                     \x20   - name: Posts\n\
+                     empty: ~\n";
+
+        let table = parse(input).expect("ERROR: Couldn't parse yaml");
+
+        assert_eq!(table["title"], Value::String("Hello: world".to_string()));
+        assert_eq!(table["draft"], Value::Boolean(false));
+        assert_eq!(table["tags"], Value::Array(vec![Value::String("rust".to_string()), Value::String("web".to_string())]));
+        assert_eq!(table["categories"], Value::Array(vec![Value::String("notes".to_string()), Value::String("til".to_string())]));
+        let params = table["params"].as_table().unwrap();
+        assert_eq!(params["hero"], Value::String("/img/hero.png".to_string()));
+        let links = params["links"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].as_table().unwrap()["url"], Value::String("/".to_string()));
+        assert_eq!(links[1].as_table().unwrap()["name"], Value::String("Posts".to_string()));
+        assert!(!table.contains_key("empty"));
+    }
+
+    #[test]
+    fn parse_block_scalars() {
+        let input = "literal: |\n\
+                     \x20 first\n\
+                     \x20   indented\n\
+                     \n\
+                     folded: >-\n\
+                     \x20 one\n\
+                     \x20 two\n\
+                     \n\
+                     \x20 three\n\
+                     date: 2023-04-08T10:17:00\n";
+
+        let table = parse(input).expect("ERROR: Couldn't parse yaml");
+
+        assert_eq!(table["literal"], Value::String("first\n  indented\n".to_string()));
+        assert_eq!(table["folded"], Value::String("one two\nthree".to_string()));
+        assert_eq!(table["date"], Value::String("2023-04-08T10:17:00".to_string()));
+    }
+
+    #[test]
+    fn parse_bad_indentation_is_error() {
+        assert_err!(parse("title: a\n  tags: b\n"));
+        assert_err!(parse("- a\n- b\n"));
+        assert_err!(parse("just text\n"));
+    }
+}
@@ -1,3 +1,3 @@
-pub(crate) mod markdown;
-pub(crate) mod metadata;
+pub mod markdown;
+pub mod metadata;
 
@@ -1,31 +1,33 @@
 use std::{collections::HashMap, net::{TcpListener, TcpStream}, io::{BufReader, BufRead, Write}};
 
-pub fn start(content_map: &HashMap<String, Vec<u8>>) {
-    let listener = TcpListener::bind("127.0.0.1:1313").expect("Couldn't bind :1313");
+pub fn start(host: &str, port: u16, content_map: &HashMap<String, Vec<u8>>) {
+    let address = format!("{host}:{port}");
+    let listener = TcpListener::bind(&address).unwrap_or_else(|_| panic!("Couldn't bind {address}"));
 
-    println!("Listening on port 1313");
+    println!("Listening on http://{address}");
     for stream in listener.incoming() {
         let stream = stream.expect("Couldn't get listener stream");
 
-        handle_stream(stream, &content_map);
+        handle_stream(stream, content_map);
     }
 }
 
 fn handle_stream(mut stream: TcpStream, content_map: &HashMap<String, Vec<u8>>) {
     let request_line = BufReader::new(&mut stream).lines().next().expect("Couldn't get next line").expect("Couldn't unwrap result");
-    handle_connection(stream, request_line, &content_map);
+    handle_connection(stream, request_line, content_map);
 }
 
 fn handle_connection(mut stream: impl Write, request_line: String, content_map: &HashMap<String, Vec<u8>>) {
     println!("{request_line}");
     let mut path = get_request_path(&request_line);
-    if path == "/" {
-        path = "/index.html".to_string();
+    // Posts live at `/posts/hello/`, served from `posts/hello/index.html`
+    if path.ends_with('/') {
+        path = format!("{path}index.html");
     }
 
-    let (status_line, content) = match get_content_for_path(&path, &content_map) {
+    let (status_line, content) = match get_content_for_path(&path, content_map) {
         None => {
-            ("HTTP/1.1 404 NOT FOUND", get_not_found_content(&content_map))
+            ("HTTP/1.1 404 NOT FOUND", get_not_found_content(content_map))
         },
         Some(t) => {
             ("HTTP/1.1 200 OK", t)
@@ -40,7 +42,7 @@ fn handle_connection(mut stream: impl Write, request_line: String, content_map:
         format!("{status_line}\r\ncontent-length: {content_length}\r\ncontent-type: {content_type}\r\n\r\n");
 
     let mut respvec = Vec::from(response.as_bytes());
-    respvec.extend_from_slice(&mut Vec::from(content));
+    respvec.extend_from_slice(&content);
 
     stream.write_all(&respvec).unwrap();
 }
@@ -144,7 +146,7 @@ mod tests {
         let response = 
             format!("{status_line}\r\ncontent-length: {content_length}\r\ncontent-type: {content_type}\r\n\r\n");
         let mut respvec = Vec::from(response.as_bytes());
-        respvec.extend_from_slice(&mut Vec::from(content));
+        respvec.extend_from_slice(&Vec::from(content));
         return respvec;
     }
 
@@ -185,6 +187,21 @@ mod tests {
         return Ok(());
     }
 
+    #[test]
+    fn test_handle_connection_dir_serves_index() -> Result<(), Error> {
+        let content = "<h1>Post 1</h1>";
+        let respvec = get_respvec("HTTP/1.1 200 OK", content, "text/html");
+
+        let mut stream = MockWriter{ content: Vec::new() };
+        let request_line = "GET /posts/post-1/ HTTP/1.1".to_string();
+        let mut content_map: HashMap<String, Vec<u8>> = HashMap::new();
+        content_map.insert("/posts/post-1/index.html".to_string(), content.as_bytes().to_vec());
+        handle_connection(&mut stream, request_line, &content_map);
+
+        assert_eq!(stream.get_content(), respvec);
+        return Ok(());
+    }
+
     #[test]
     fn test_get_content_for_path() {
         let path = "/".to_string();
@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+
+use crate::parsers::metadata::Value;
+
+use super::Page;
+use super::page::url_to_output_path;
+
+/// What a section's list page is sorted by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortBy {
+    Date,
+    Weight,
+    Title,
+    /// Any other front matter key, e.g. `rating`
+    Param(String),
+}
+
+impl SortBy {
+    pub fn parse(input: &str) -> SortBy {
+        return match input {
+            "date" => SortBy::Date,
+            "weight" => SortBy::Weight,
+            "title" => SortBy::Title,
+            key => SortBy::Param(key.to_string()),
+        };
+    }
+
+    /// Dates sort newest first, everything else ascending.
+    fn is_descending(&self) -> bool {
+        return *self == SortBy::Date;
+    }
+}
+
+/// Sorts the pages of a list. `descending` overrides the default order of the
+/// key. Pages that tie keep their path order, and pages without the front
+/// matter key go last.
+pub fn sort_pages(pages: &mut [&Page], sort_by: &SortBy, descending: Option<bool>) {
+    let descending = descending.unwrap_or(sort_by.is_descending());
+    pages.sort_by(|a, b| {
+        let ordering = match sort_by {
+            SortBy::Date => a.metadata.get_date().cmp(b.metadata.get_date()),
+            SortBy::Weight => a.metadata.get_weight().cmp(&b.metadata.get_weight()),
+            SortBy::Title => a.get_title().to_lowercase().cmp(&b.get_title().to_lowercase()),
+            SortBy::Param(key) => match (a.metadata.get_param(key), b.metadata.get_param(key)) {
+                (Some(a), Some(b)) => compare_values(a, b),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        return match descending {
+            true => ordering.reverse(),
+            false => ordering,
+        };
+    });
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    return match (a, b) {
+        (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+        (Value::Integer(a), Value::Float(b)) => (*a as f64).total_cmp(b),
+        (Value::Float(a), Value::Integer(b)) => a.total_cmp(&(*b as f64)),
+        (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    };
+}
+
+/// One page of a paginated list.
+#[derive(Debug)]
+pub struct Pager<'a> {
+    /// Counts from 1
+    pub number: usize,
+    pub total: usize,
+    pub pages: Vec<&'a Page>,
+    pub url: String,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
+}
+
+impl Pager<'_> {
+    pub fn get_output_path(&self) -> String {
+        return url_to_output_path(&self.url);
+    }
+}
+
+/// Splits a list into pagers of `page_size` pages, or a single pager if it is
+/// 0. The first pager is at `first_url`, the rest at `{section_url}page/2/`
+/// and so on. An empty list still gets a pager, so the list page renders.
+pub fn paginate<'a>(pages: &[&'a Page], page_size: usize, first_url: &str, section_url: &str) -> Vec<Pager<'a>> {
+    let chunks = match page_size {
+        0 => vec![pages.to_vec()],
+        _ => pages.chunks(page_size).map(|x| x.to_vec()).collect(),
+    };
+    let total = chunks.len().max(1);
+    let url = |number: usize| match number {
+        1 => first_url.to_string(),
+        _ => format!("{section_url}page/{number}/"),
+    };
+
+    let mut pagers = Vec::new();
+    for (i, pages) in chunks.into_iter().enumerate() {
+        let number = i + 1;
+        pagers.push(Pager {
+            number,
+            total,
+            pages,
+            url: url(number),
+            prev_url: (number > 1).then(|| url(number - 1)),
+            next_url: (number < total).then(|| url(number + 1)),
+        });
+    }
+    if pagers.is_empty() {
+        pagers.push(Pager { number: 1, total, pages: Vec::new(), url: url(1), prev_url: None, next_url: None });
+    }
+
+    return pagers;
+}
+
+#[cfg(test)]
+mod list_tests {
+    use crate::parsers::metadata::{AfDateTime, Metadata};
+    use crate::site::Page;
+
+    use super::{SortBy, paginate, sort_pages};
+
+    fn page(path: &str, front_matter: &str) -> Page {
+        let metadata = Metadata::parse(front_matter, AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
+        return Page::new(path, "", metadata, "", AfDateTime::now());
+    }
+
+    fn paths(pages: &[&Page]) -> Vec<String> {
+        return pages.iter().map(|x| x.get_name().to_string()).collect();
+    }
+
+    #[test]
+    fn sort_pages_by_date_weight_title_and_key() {
+        let a = page("posts/a.md", "+++\ndate = 2023-01-01\nweight = 2\ntitle = \"Zebra\"\nrating = 5\n+++\n");
+        let b = page("posts/b.md", "+++\ndate = 2023-03-01\nweight = 1\ntitle = \"apple\"\n+++\n");
+        let c = page("posts/c.md", "+++\ndate = 2023-02-01\nweight = 3\ntitle = \"Mango\"\nrating = 3\n+++\n");
+        let mut pages = vec![&a, &b, &c];
+
+        sort_pages(&mut pages, &SortBy::Date, None);
+        assert_eq!(paths(&pages), vec!["b", "c", "a"]);
+        sort_pages(&mut pages, &SortBy::Weight, None);
+        assert_eq!(paths(&pages), vec!["b", "a", "c"]);
+        sort_pages(&mut pages, &SortBy::Title, None);
+        assert_eq!(paths(&pages), vec!["b", "c", "a"]);
+        sort_pages(&mut pages, &SortBy::parse("rating"), Some(true));
+        assert_eq!(paths(&pages), vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn paginate_splits_pages_and_links_pagers() {
+        let pages = ["a", "b", "c", "d", "e"].map(|x| page(&format!("posts/{x}.md"), "+++\n+++\n"));
+        let pages = pages.iter().collect::<Vec<&Page>>();
+
+        let pagers = paginate(&pages, 2, "/posts.html", "/posts/");
+
+        assert_eq!(pagers.len(), 3);
+        assert_eq!(paths(&pagers[1].pages), vec!["c", "d"]);
+        assert_eq!(pagers[1].url, "/posts/page/2/");
+        assert_eq!(pagers[1].get_output_path(), "posts/page/2/index.html");
+        assert_eq!(pagers[1].prev_url.as_deref(), Some("/posts.html"));
+        assert_eq!(pagers[1].next_url.as_deref(), Some("/posts/page/3/"));
+        assert_eq!(pagers[2].total, 3);
+        assert_eq!(pagers[2].next_url, None);
+        assert_eq!(paginate(&pages, 0, "/posts.html", "/posts/").len(), 1);
+        assert_eq!(paginate(&[], 2, "/posts.html", "/posts/").len(), 1);
+    }
+}
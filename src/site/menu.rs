@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::Value;
+
+use super::{Page, Site};
+
+/// A link in a navigation menu, from a page's front matter or the config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuEntry {
+    pub name: String,
+    pub url: String,
+    pub weight: i64,
+    /// The name of the entry this one is nested under
+    pub parent: Option<String>,
+    pub children: Vec<MenuEntry>,
+}
+
+impl MenuEntry {
+    /// The entry as a layout sees it, `active` when it links to `current_url`
+    /// and `child_active` when one of the entries under it does.
+    pub fn to_value(&self, current_url: &str) -> Value {
+        let children = self.children.iter().map(|x| x.to_value(current_url)).collect();
+        return Value::Table(BTreeMap::from([
+            ("name".to_string(), Value::String(self.name.clone())),
+            ("url".to_string(), Value::String(self.url.clone())),
+            ("weight".to_string(), Value::Integer(self.weight)),
+            ("active".to_string(), Value::Boolean(self.url == current_url)),
+            ("child_active".to_string(), Value::Boolean(self.has_active_child(current_url))),
+            ("children".to_string(), Value::Array(children)),
+        ]));
+    }
+
+    pub fn has_active_child(&self, current_url: &str) -> bool {
+        return self.children.iter().any(|x| x.url == current_url || x.has_active_child(current_url));
+    }
+}
+
+/// Every menu of the site by name. Pages join a menu with `menu = "main"`,
+/// `menu = ["main", "footer"]` or a table like
+/// `menu = { main = { name = "Home", weight = 1, parent = "About" } }`, and
+/// the config adds links under `[menu.<name>]`. Entries are sorted by weight
+/// then name, and nested under the entry named by their `parent`.
+pub fn build_menus(site: &Site) -> Result<BTreeMap<String, Vec<MenuEntry>>, String> {
+    let mut flat: BTreeMap<String, Vec<MenuEntry>> = BTreeMap::new();
+    for page in site.get_pages() {
+        for (menu, entry) in get_page_entries(page)? {
+            flat.entry(menu).or_default().push(entry);
+        }
+    }
+    for (menu, links) in &site.config.menus {
+        for link in links {
+            flat.entry(menu.clone()).or_default().push(MenuEntry {
+                name: link.name.clone(),
+                url: link.url.clone(),
+                weight: link.weight,
+                parent: link.parent.clone(),
+                children: Vec::new(),
+            });
+        }
+    }
+
+    let mut menus = BTreeMap::new();
+    for (menu, mut entries) in flat {
+        entries.sort_by(|a, b| (a.weight, &a.name, &a.url).cmp(&(b.weight, &b.name, &b.url)));
+        for entry in &entries {
+            if let Some(parent) = &entry.parent {
+                if !entries.iter().any(|x| &x.name == parent) {
+                    return Err(format!("ERROR: Menu entry \"{}\" in menu \"{menu}\" has an unknown parent \"{parent}\"", entry.name));
+                }
+            }
+        }
+
+        let total = entries.len();
+        let tree = nest(None, &mut entries);
+        if count(&tree) != total {
+            return Err(format!("ERROR: The parents of menu \"{menu}\" form a loop"));
+        }
+        menus.insert(menu, tree);
+    }
+
+    return Ok(menus);
+}
+
+/// Takes the entries under `parent` out of `entries`, with their own children.
+fn nest(parent: Option<&str>, entries: &mut Vec<MenuEntry>) -> Vec<MenuEntry> {
+    let (mut level, rest): (Vec<MenuEntry>, Vec<MenuEntry>) = entries.drain(..).partition(|x| x.parent.as_deref() == parent);
+    *entries = rest;
+    for entry in level.iter_mut() {
+        entry.children = nest(Some(&entry.name), entries);
+    }
+    return level;
+}
+
+fn count(entries: &[MenuEntry]) -> usize {
+    return entries.iter().map(|x| 1 + count(&x.children)).sum();
+}
+
+fn get_page_entries(page: &Page) -> Result<Vec<(String, MenuEntry)>, String> {
+    let Some(menu) = page.metadata.get_param("menu") else {
+        return Ok(Vec::new());
+    };
+    let invalid = || format!("ERROR: {}: menu has to be a menu name, a list of them or a table", page.path);
+    let entry = |name: Option<&str>, weight: Option<i64>, parent: Option<&str>| MenuEntry {
+        name: name.unwrap_or(page.get_title()).to_string(),
+        url: page.url.clone(),
+        weight: weight.unwrap_or(page.metadata.get_weight()),
+        parent: parent.map(|x| x.to_string()),
+        children: Vec::new(),
+    };
+
+    let mut entries = Vec::new();
+    match menu {
+        Value::String(menu) => entries.push((menu.clone(), entry(None, None, None))),
+        Value::Array(menus) => {
+            for menu in menus {
+                let menu = menu.as_str().ok_or_else(invalid)?;
+                entries.push((menu.to_string(), entry(None, None, None)));
+            }
+        }
+        Value::Table(menus) => {
+            for (menu, settings) in menus {
+                let Value::Table(settings) = settings else {
+                    return Err(invalid());
+                };
+                let setting = |key: &str| settings.get(key);
+                let name = setting("name").map(|x| x.as_str().ok_or_else(invalid)).transpose()?;
+                let weight = setting("weight").map(|x| x.as_integer().ok_or_else(invalid)).transpose()?;
+                let parent = setting("parent").map(|x| x.as_str().ok_or_else(invalid)).transpose()?;
+                entries.push((menu.clone(), entry(name, weight, parent)));
+            }
+        }
+        _ => return Err(invalid()),
+    }
+
+    return Ok(entries);
+}
+
+#[cfg(test)]
+mod menu_tests {
+    use claim::assert_err;
+
+    use crate::config::{MenuLink, SiteConfig};
+    use crate::parsers::metadata::{AfDateTime, Metadata, Value};
+    use crate::site::{Page, Site};
+
+    use super::build_menus;
+
+    fn page(path: &str, front_matter: &str) -> Page {
+        let metadata = Metadata::parse(front_matter, AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
+        return Page::new(path, "", metadata, "", AfDateTime::now());
+    }
+
+    fn link(name: &str, url: &str, weight: i64, parent: Option<&str>) -> MenuLink {
+        return MenuLink { name: name.to_string(), url: url.to_string(), weight, parent: parent.map(|x| x.to_string()) };
+    }
+
+    #[test]
+    fn build_menus_from_pages_and_config() {
+        let mut config = SiteConfig::default();
+        config.menus.insert("main".to_string(), vec![link("GitHub", "https://github.com/me", 5, Some("About"))]);
+        let pages = vec![
+            page("index.md", "+++\ntitle = \"Welcome\"\nmenu = { main = { name = \"Home\", weight = 1 } }\n+++\n"),
+            page("about.md", "+++\ntitle = \"About\"\nmenu = [\"main\", \"footer\"]\nweight = 2\n+++\n"),
+            page("posts/team.md", "+++\ntitle = \"Team\"\nmenu = { main = { parent = \"About\" } }\n+++\n"),
+            page("contact.md", "+++\ntitle = \"Contact\"\n+++\n"),
+        ];
+        let site = Site::new(config, pages);
+
+        let menus = build_menus(&site).expect("ERROR: Couldn't build menus");
+
+        let main = &menus["main"];
+        assert_eq!(main.iter().map(|x| x.name.as_str()).collect::<Vec<&str>>(), vec!["Home", "About"]);
+        assert_eq!(main[0].url, "/index.html");
+        assert_eq!(main[1].children.iter().map(|x| x.name.as_str()).collect::<Vec<&str>>(), vec!["Team", "GitHub"]);
+        assert_eq!(menus["footer"].len(), 1);
+
+        let Value::Table(about) = main[1].to_value("/posts/team/") else { panic!("ERROR: Entry isn't a table") };
+        assert_eq!(about["active"], Value::Boolean(false));
+        assert_eq!(about["child_active"], Value::Boolean(true));
+    }
+
+    #[test]
+    fn build_menus_with_invalid_parent_is_error() {
+        let unknown = Site::new(SiteConfig::default(), vec![page("a.md", "+++\nmenu = { main = { parent = \"Nope\" } }\n+++\n")]);
+        let looped = Site::new(SiteConfig::default(), vec![
+            page("a.md", "+++\ntitle = \"A\"\nmenu = { main = { parent = \"B\" } }\n+++\n"),
+            page("b.md", "+++\ntitle = \"B\"\nmenu = { main = { parent = \"A\" } }\n+++\n"),
+        ]);
+        let invalid = Site::new(SiteConfig::default(), vec![page("a.md", "+++\nmenu = 3\n+++\n")]);
+
+        assert_err!(build_menus(&unknown));
+        assert_err!(build_menus(&looped));
+        assert_err!(build_menus(&invalid));
+    }
+}
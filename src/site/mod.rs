@@ -0,0 +1,11 @@
+mod list;
+mod menu;
+mod page;
+mod section;
+mod site;
+
+pub use list::{SortBy, Pager, sort_pages, paginate};
+pub use menu::{MenuEntry, build_menus};
+pub use page::{Page, SUMMARY_DIVIDER};
+pub use section::Section;
+pub use site::Site;
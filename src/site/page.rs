@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use crate::parsers::metadata::{AfDateTime, Metadata, Value};
+
+/// Marks the end of a page's summary in its markdown.
+pub static SUMMARY_DIVIDER: &str = "<!--more-->";
+
+/// A content file with everything the layouts need, parsed and rendered once.
+#[derive(Debug)]
+pub struct Page {
+    /// Path relative to the content dir, e.g. `posts/hello.md`
+    pub path: String,
+    /// The top level dir the page is in, e.g. `posts`, or empty for pages
+    /// at the root of the content dir
+    pub section: String,
+    /// Site relative url, `/about.html` for a page at the root of the content
+    /// dir and `/posts/hello/` for one in a section
+    pub url: String,
+    /// `url` prefixed with the site's `base_url`
+    pub permalink: String,
+    pub metadata: Metadata,
+    /// The markdown after the front matter
+    pub raw_content: String,
+    /// The markdown rendered to html
+    pub content: String,
+    /// Html for the part before `<!--more-->`, else the first paragraph
+    pub summary: String,
+    /// The content as plain text, e.g. for a search index
+    pub text: String,
+    /// The summary as plain text, e.g. for a meta description
+    pub summary_text: String,
+    /// The `lastmod` front matter key, else when the file was last modified
+    pub lastmod: AfDateTime,
+}
+
+impl Page {
+    pub fn new(path: &str, base_url: &str, metadata: Metadata, raw_content: &str, lastmod: AfDateTime) -> Page {
+        let section = match path.split_once('/') {
+            Some((section, _)) => section.to_string(),
+            None => String::default(),
+        };
+        let stem = path.strip_suffix(".md").unwrap_or(path);
+        // A `slug` replaces the file name, but not the dirs
+        let stem = match (metadata.get_slug(), stem.rsplit_once('/')) {
+            (Some(slug), Some((dir, _))) => format!("{dir}/{slug}"),
+            (Some(slug), None) => slug.to_string(),
+            (None, _) => stem.to_string(),
+        };
+        let url = match section.is_empty() {
+            true => format!("/{stem}.html"),
+            false => format!("/{stem}/"),
+        };
+
+        return Page {
+            path: path.to_string(),
+            section,
+            permalink: format!("{base_url}{url}"),
+            url,
+            metadata,
+            raw_content: raw_content.to_string(),
+            content: String::default(),
+            summary: String::default(),
+            text: String::default(),
+            summary_text: String::default(),
+            lastmod,
+        };
+    }
+
+    /// Sets the rendered html. `summary_html` is the rendered markdown before
+    /// `<!--more-->`, if the page has one.
+    pub fn set_content(&mut self, content: String, summary_html: Option<String>) {
+        self.summary = match summary_html {
+            Some(summary) => summary,
+            None => first_paragraph(&content).to_string(),
+        };
+        self.content = content;
+    }
+
+    /// Sets the content and summary rendered to plain text.
+    pub fn set_text(&mut self, text: String, summary_text: String) {
+        self.text = text;
+        self.summary_text = summary_text;
+    }
+
+    /// The markdown before `<!--more-->`, if the page marks its summary.
+    pub fn get_raw_summary(&self) -> Option<&str> {
+        return self.raw_content.split_once(SUMMARY_DIVIDER).map(|(summary, _)| summary);
+    }
+
+    /// The markdown of the summary like `summary` picks it: the part before
+    /// `<!--more-->`, else the first paragraph.
+    pub fn get_raw_summary_or_first_paragraph(&self) -> &str {
+        if let Some(summary) = self.get_raw_summary() {
+            return summary;
+        }
+        return self.raw_content.split("\n\n")
+            .map(|x| x.trim())
+            .find(|x| !x.is_empty() && !x.starts_with('#') && !x.starts_with("```"))
+            .unwrap_or_default();
+    }
+
+    /// Where the page is written in the public dir, e.g.
+    /// `posts/hello/index.html`.
+    pub fn get_output_path(&self) -> String {
+        return url_to_output_path(&self.url);
+    }
+
+    /// The page as the `page` variable of a layout.
+    pub fn to_value(&self) -> Value {
+        let strings = |items: &[String]| Value::Array(items.iter().map(|x| Value::String(x.clone())).collect());
+        return Value::Table(BTreeMap::from([
+            ("path".to_string(), Value::String(self.path.clone())),
+            ("name".to_string(), Value::String(self.get_name().to_string())),
+            ("section".to_string(), Value::String(self.section.clone())),
+            ("url".to_string(), Value::String(self.url.clone())),
+            ("permalink".to_string(), Value::String(self.permalink.clone())),
+            ("title".to_string(), Value::String(self.get_title().to_string())),
+            ("description".to_string(), Value::String(self.metadata.get_description().unwrap_or_default().to_string())),
+            ("author".to_string(), Value::String(self.metadata.get_author().unwrap_or_default().to_string())),
+            ("date".to_string(), Value::String(self.metadata.get_date().to_string())),
+            ("lastmod".to_string(), Value::String(self.lastmod.to_string())),
+            ("weight".to_string(), Value::Integer(self.metadata.get_weight())),
+            ("tags".to_string(), strings(self.metadata.get_tags())),
+            ("categories".to_string(), strings(self.metadata.get_categories())),
+            ("params".to_string(), Value::Table(self.metadata.get_params().clone())),
+            ("summary".to_string(), Value::String(self.summary.clone())),
+            ("summary_text".to_string(), Value::String(self.summary_text.clone())),
+            ("content".to_string(), Value::String(self.content.clone())),
+            ("text".to_string(), Value::String(self.text.clone())),
+        ]));
+    }
+
+    /// The file name without `.md`, e.g. `hello` for `posts/hello.md`.
+    pub fn get_name(&self) -> &str {
+        let file_name = self.path.rsplit('/').next().unwrap_or(&self.path);
+        return file_name.strip_suffix(".md").unwrap_or(file_name);
+    }
+
+    /// The `title` front matter key, else the file name.
+    pub fn get_title(&self) -> &str {
+        return self.metadata.get_title().unwrap_or(self.get_name());
+    }
+}
+
+/// The file a site relative url is served from, `index.html` for a dir.
+pub(super) fn url_to_output_path(url: &str) -> String {
+    let path = url.trim_start_matches('/');
+    return match path.is_empty() || path.ends_with('/') {
+        true => format!("{path}index.html"),
+        false => path.to_string(),
+    };
+}
+
+fn first_paragraph(html: &str) -> &str {
+    let Some(start) = html.find("<p>") else {
+        return "";
+    };
+    return match html[start..].find("</p>") {
+        Some(end) => &html[start..start + end + "</p>".len()],
+        None => &html[start..],
+    };
+}
+
+#[cfg(test)]
+mod page_tests {
+    use crate::parsers::metadata::{AfDateTime, Metadata};
+
+    use super::Page;
+
+    #[test]
+    fn new_page_has_section_and_urls() {
+        let page = Page::new("posts/hello.md", "https://example.com", Metadata::default(), "# Hello", AfDateTime::now());
+
+        assert_eq!(page.section, "posts");
+        assert_eq!(page.url, "/posts/hello/");
+        assert_eq!(page.permalink, "https://example.com/posts/hello/");
+        assert_eq!(page.get_output_path(), "posts/hello/index.html");
+        assert_eq!(page.get_title(), "hello");
+
+        let about = Page::new("about.md", "", Metadata::default(), "", AfDateTime::now());
+        assert_eq!(about.section, "");
+        assert_eq!(about.get_output_path(), "about.html");
+    }
+
+    #[test]
+    fn new_page_with_slug_replaces_file_name() {
+        let metadata = Metadata::parse("+++\nslug = \"hi\"\n+++\n", AfDateTime::now(), 0).expect("ERROR: Couldn't parse metadata");
+
+        let page = Page::new("posts/2023/hello.md", "", metadata, "", AfDateTime::now());
+
+        assert_eq!(page.section, "posts");
+        assert_eq!(page.url, "/posts/2023/hi/");
+    }
+
+    #[test]
+    fn summary_is_divider_or_first_paragraph() {
+        let mut page = Page::new("index.md", "", Metadata::default(), "Intro\n<!--more-->\nRest", AfDateTime::now());
+        assert_eq!(page.get_raw_summary(), Some("Intro\n"));
+        page.set_content("<p>Intro</p><p>Rest</p>".to_string(), Some("<p>Intro</p>".to_string()));
+        assert_eq!(page.summary, "<p>Intro</p>");
+
+        page.set_content("<h1>Title</h1><p>First</p><p>Second</p>".to_string(), None);
+        assert_eq!(page.summary, "<p>First</p>");
+
+        let page = Page::new("index.md", "", Metadata::default(), "# Title\n\nFirst\nline\n\nSecond", AfDateTime::now());
+        assert_eq!(page.get_raw_summary_or_first_paragraph(), "First\nline");
+    }
+}
@@ -0,0 +1,15 @@
+/// A top level dir in the content dir, e.g. `content/posts/`.
+#[derive(Debug, PartialEq)]
+pub struct Section {
+    pub name: String,
+    /// `posts.md` next to `posts/`, which renders the section's own page
+    pub index: Option<String>,
+    /// Paths of the pages in the section, sorted
+    pub pages: Vec<String>,
+}
+
+impl Section {
+    pub fn new(name: &str) -> Section {
+        return Section { name: name.to_string(), index: None, pages: Vec::new() };
+    }
+}
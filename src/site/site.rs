@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use crate::config::SiteConfig;
+use crate::parsers::metadata::Value;
+
+use super::{Page, Section};
+
+/// Everything a build renders: the config, every page and the sections they
+/// belong to. Built once, then read by the layouts, menus and sitemap.
+#[derive(Debug)]
+pub struct Site {
+    pub config: SiteConfig,
+    /// Sorted by path
+    pages: Vec<Page>,
+    /// Sorted by name
+    sections: Vec<Section>,
+}
+
+impl Site {
+    pub fn new(config: SiteConfig, mut pages: Vec<Page>) -> Site {
+        pages.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut sections: Vec<Section> = Vec::new();
+        for page in pages.iter().filter(|x| !x.section.is_empty()) {
+            if sections.last().map(|x| &x.name) != Some(&page.section) {
+                sections.push(Section::new(&page.section));
+            }
+            let section = sections.last_mut().expect("ERROR: Section is missing");
+            section.pages.push(page.path.clone());
+        }
+        for section in sections.iter_mut() {
+            let index = format!("{}.md", section.name);
+            if pages.iter().any(|x| x.path == index) {
+                section.index = Some(index);
+            }
+        }
+
+        return Site { config, pages, sections };
+    }
+
+    pub fn get_pages(&self) -> &[Page] {
+        return &self.pages;
+    }
+
+    pub fn get_page(&self, path: &str) -> Option<&Page> {
+        return self.pages.iter().find(|x| x.path == path);
+    }
+
+    pub fn get_sections(&self) -> &[Section] {
+        return &self.sections;
+    }
+
+    pub fn get_section(&self, name: &str) -> Option<&Section> {
+        return self.sections.iter().find(|x| x.name == name);
+    }
+
+    /// The site as the `site` variable of a layout, with every page and
+    /// section.
+    pub fn to_value(&self) -> Value {
+        let sections = self.sections.iter().map(|section| {
+            let pages = self.get_section_pages(section).iter().map(|x| x.to_value()).collect();
+            Value::Table(BTreeMap::from([
+                ("name".to_string(), Value::String(section.name.clone())),
+                ("pages".to_string(), Value::Array(pages)),
+            ]))
+        }).collect();
+
+        return Value::Table(BTreeMap::from([
+            ("title".to_string(), Value::String(self.config.title.clone())),
+            ("base_url".to_string(), Value::String(self.config.base_url.clone())),
+            ("language".to_string(), Value::String(self.config.language.clone())),
+            ("params".to_string(), Value::Table(self.config.params.clone())),
+            ("pages".to_string(), Value::Array(self.pages.iter().map(|x| x.to_value()).collect())),
+            ("sections".to_string(), Value::Array(sections)),
+        ]));
+    }
+
+    /// The pages of a section, in path order.
+    pub fn get_section_pages(&self, section: &Section) -> Vec<&Page> {
+        return section.pages.iter().filter_map(|x| self.get_page(x)).collect();
+    }
+}
+
+#[cfg(test)]
+mod site_tests {
+    use crate::config::SiteConfig;
+    use crate::parsers::metadata::{AfDateTime, Metadata};
+    use crate::site::Page;
+
+    use super::Site;
+
+    fn page(path: &str) -> Page {
+        return Page::new(path, "", Metadata::default(), "", AfDateTime::now());
+    }
+
+    #[test]
+    fn new_site_groups_pages_into_sections() {
+        let pages = vec![page("posts/b.md"), page("index.md"), page("posts.md"), page("posts/a.md"), page("docs/intro.md")];
+
+        let site = Site::new(SiteConfig::default(), pages);
+
+        assert_eq!(site.get_pages().iter().map(|x| x.path.as_str()).collect::<Vec<&str>>(), vec!["docs/intro.md", "index.md", "posts.md", "posts/a.md", "posts/b.md"]);
+        assert_eq!(site.get_sections().len(), 2);
+        let posts = site.get_section("posts").expect("ERROR: No posts section");
+        assert_eq!(posts.index, Some("posts.md".to_string()));
+        assert_eq!(site.get_section_pages(posts).iter().map(|x| x.get_name()).collect::<Vec<&str>>(), vec!["a", "b"]);
+        assert_eq!(site.get_section("docs").and_then(|x| x.index.as_ref()), None);
+    }
+}